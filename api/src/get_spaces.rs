@@ -0,0 +1,17 @@
+use anyhow::Result;
+use base::parser::{GetSpacesData, parse_spaces};
+
+/// Loads every workspace the token can access, grouped by user ID (a single token can be
+/// logged in to several accounts). Requires the `token_v2` cookie of a logged in user.
+pub async fn get(token: &str) -> Result<GetSpacesData<'static>> {
+    let client = reqwest::Client::new();
+    let res = client.post("https://www.notion.so/api/v3/getSpaces")
+        .body("{}")
+        .header("content-type", "application/json")
+        .header("cookie", format!("token_v2={}", token))
+        .send().await?
+        .error_for_status()?
+        .text().await?;
+
+    parse_spaces(res)
+}
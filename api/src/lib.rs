@@ -1 +1,5 @@
-pub mod load_page_chunks;
\ No newline at end of file
+pub mod get_spaces;
+pub mod load_page_chunks;
+pub mod load_user_content;
+pub mod search;
+pub mod v1;
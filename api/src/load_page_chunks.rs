@@ -1,14 +1,40 @@
 use anyhow::Result;
-use base::parser::{LoadPageChunkData, parse};
+use base::async_renderer::BlockSource;
+use base::id::BlockId;
+use base::parser::{parse_slice, BlockTableType, LoadPageChunkData};
+use bytes::Bytes;
+use futures_util::future::BoxFuture;
 
-pub async fn load(id: &str) -> Result<LoadPageChunkData> {
+/// Fetches the `loadPageChunk` response for a page, without parsing it. Parse it with
+/// `parse_slice`, which borrows strings from the response instead of copying them.
+pub async fn load_bytes(id: &BlockId) -> Result<Bytes> {
     let client = reqwest::Client::new();
+    let id = &id.to_string();
     let body = r#"{"pageId":""#.to_string() + id + r#"","limit":100000,"cursor":{"stack":[[{"table":"block","id":""# + id + r#"","index":0}]]},"chunkNumber":0,"verticalColumns":false}"#;
     let res = client.post("https://www.notion.so/api/v3/loadPageChunk")
         .body(body)
         .header("content-type", "application/json")
-        .send().await?
-        .text().await?;
+        .send().await?;
 
-    Ok(parse(res)?)
-}
\ No newline at end of file
+    Ok(res.bytes().await?)
+}
+
+/// Fetches and parses the `loadPageChunk` response for a page. The result owns its strings, so
+/// they're copied out of the response; use `load_bytes` and `parse_slice` to borrow them instead.
+pub async fn load(id: &BlockId) -> Result<LoadPageChunkData<'static>> {
+    let bytes = load_bytes(id).await?;
+    let result = parse_slice(&bytes)?;
+    Ok(LoadPageChunkData {
+        record_map: result.record_map.into_owned(),
+    })
+}
+
+/// Fetches missing blocks for `AsyncRenderer` with `loadPageChunk`, which returns the block along
+/// with the blocks under it, so a whole subtree is usually filled in by one fetch.
+pub struct PageChunkSource;
+
+impl BlockSource for PageChunkSource {
+    fn fetch<'s>(&'s self, block_id: &'s BlockId) -> BoxFuture<'s, Result<BlockTableType<'static>>> {
+        Box::pin(async move { Ok(load(block_id).await?.record_map.block) })
+    }
+}
@@ -0,0 +1,18 @@
+use anyhow::Result;
+use base::parser::{LoadUserContentData, parse_user_content};
+
+/// Loads the logged in user's record map, including the workspaces they belong to (`space`),
+/// their views of those workspaces (`space_view`), and the workspaces' top-level pages (`block`).
+/// Requires the `token_v2` cookie of a logged in user.
+pub async fn load(token: &str) -> Result<LoadUserContentData<'static>> {
+    let client = reqwest::Client::new();
+    let res = client.post("https://www.notion.so/api/v3/loadUserContent")
+        .body("{}")
+        .header("content-type", "application/json")
+        .header("cookie", format!("token_v2={}", token))
+        .send().await?
+        .error_for_status()?
+        .text().await?;
+
+    parse_user_content(res)
+}
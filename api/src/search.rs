@@ -0,0 +1,103 @@
+use anyhow::Result;
+use base::parser::{parse_search, SearchData};
+use serde::Serialize;
+
+/// The order Notion returns search results in.
+#[derive(Serialize, Debug, Clone, Copy)]
+pub enum SearchSort {
+    Relevance,
+    LastEdited,
+    Created,
+}
+
+/// One end of a date range filter. Dates are in `YYYY-MM-DD` form.
+#[derive(Serialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DateBound {
+    Date { start_date: String },
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct DateRange {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub starting: Option<DateBound>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ending: Option<DateBound>,
+}
+
+impl DateRange {
+    /// Creates a range between two optional `YYYY-MM-DD` dates. Passing `None` leaves that
+    /// end of the range open.
+    pub fn new(start: Option<&str>, end: Option<&str>) -> Self {
+        let bound = |date: &str| DateBound::Date {
+            start_date: date.to_string(),
+        };
+
+        DateRange {
+            starting: start.map(bound),
+            ending: end.map(bound),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchFilters {
+    pub is_deleted_only: bool,
+    pub exclude_templates: bool,
+    pub is_navigable_only: bool,
+    pub require_edit_permissions: bool,
+    /// Only return blocks nested somewhere under one of these block IDs.
+    pub ancestors: Vec<String>,
+    /// Only return blocks created by one of these user IDs.
+    pub created_by: Vec<String>,
+    pub edited_by: Vec<String>,
+    pub last_edited_time: DateRange,
+    pub created_time: DateRange,
+}
+
+/// The body sent to `/api/v3/search`. Create one with `new`, then adjust the public fields
+/// as needed before passing it to `search`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchQuery {
+    #[serde(rename = "type")]
+    search_type: &'static str,
+    pub query: String,
+    pub space_id: String,
+    pub limit: i64,
+    pub filters: SearchFilters,
+    pub sort: SearchSort,
+    source: &'static str,
+}
+
+impl SearchQuery {
+    /// Creates a query for `query` in the workspace with the ID `space_id`, sorted by relevance,
+    /// with no filters and a limit of 20 results.
+    pub fn new(space_id: &str, query: &str) -> Self {
+        SearchQuery {
+            search_type: "BlocksInSpace",
+            query: query.to_string(),
+            space_id: space_id.to_string(),
+            limit: 20,
+            filters: SearchFilters::default(),
+            sort: SearchSort::Relevance,
+            source: "quick_find",
+        }
+    }
+}
+
+/// Searches a workspace for blocks. Search is only available to members of the workspace,
+/// so this requires the `token_v2` cookie of a logged in user.
+pub async fn search(token: &str, query: &SearchQuery) -> Result<SearchData<'static>> {
+    let client = reqwest::Client::new();
+    let res = client.post("https://www.notion.so/api/v3/search")
+        .body(serde_json::to_string(query)?)
+        .header("content-type", "application/json")
+        .header("cookie", format!("token_v2={}", token))
+        .send().await?
+        .error_for_status()?
+        .text().await?;
+
+    parse_search(res)
+}
@@ -0,0 +1,478 @@
+//! An adapter for Notion's official API (`https://api.notion.com/v1`), which uses integration
+//! tokens instead of the `token_v2` cookie. Its responses are converted into the same block table
+//! `loadPageChunk` returns, so they can be rendered with `Renderer` as-is.
+//!
+//! Use `load` to fetch a page and all of its blocks, or `BlockTableBuilder` to convert responses
+//! that were fetched some other way.
+
+use anyhow::Result;
+use base::id::BlockId;
+use base::parser::{
+    from_color, BaseValueType, BlockFormatType, BlockTableType, BlockType, ColorType, ColumnFormat,
+    ContextFormat, Either, FormatType, FormattedText, ImageFormat, ImageProperties,
+    NoContextFormat, PageProperties, RootBlockType, TextProperties, TodoProperties, YesOrNo,
+};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// The version of the API the types in this module are written against.
+pub const NOTION_VERSION: &str = "2022-06-28";
+
+#[derive(Deserialize, Debug)]
+pub struct Annotations {
+    pub bold: bool,
+    pub italic: bool,
+    pub strikethrough: bool,
+    pub underline: bool,
+    pub code: bool,
+    pub color: String,
+}
+
+/// A run of rich text. Mentions and equations are kept as their `plain_text`.
+#[derive(Deserialize, Debug)]
+pub struct RichText {
+    pub plain_text: String,
+    pub href: Option<String>,
+    pub annotations: Annotations,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PartialUser {
+    pub id: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Parent {
+    PageId { page_id: BlockId },
+    BlockId { block_id: BlockId },
+    DatabaseId { database_id: BlockId },
+    Workspace,
+    #[serde(other)]
+    Other,
+}
+
+/// A block from `/v1/blocks/{id}/children`. The type-specific part of the block (eg. the
+/// `paragraph` object of a paragraph) is kept as JSON in `content`, under the block's type.
+#[derive(Deserialize, Debug)]
+pub struct Block {
+    pub id: BlockId,
+    pub parent: Option<Parent>,
+    pub created_time: String,
+    pub last_edited_time: String,
+    pub created_by: Option<PartialUser>,
+    pub last_edited_by: Option<PartialUser>,
+    pub has_children: bool,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(rename = "type")]
+    pub block_type: String,
+    #[serde(flatten)]
+    pub content: HashMap<String, Value>,
+}
+
+/// A page from `/v1/pages/{id}`. Only the title property is used.
+#[derive(Deserialize, Debug)]
+pub struct Page {
+    pub id: BlockId,
+    pub parent: Parent,
+    pub created_time: String,
+    pub last_edited_time: String,
+    pub created_by: Option<PartialUser>,
+    pub last_edited_by: Option<PartialUser>,
+    #[serde(default)]
+    pub archived: bool,
+    pub properties: HashMap<String, Value>,
+}
+
+/// A page of results from a paginated endpoint.
+#[derive(Deserialize, Debug)]
+pub struct List<T> {
+    pub results: Vec<T>,
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+}
+
+/// The fields shared by most block types.
+#[derive(Deserialize, Default)]
+struct TextContent {
+    #[serde(default)]
+    rich_text: Vec<RichText>,
+    color: Option<String>,
+    checked: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct FileUrl {
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct ImageContent {
+    #[serde(default)]
+    caption: Vec<RichText>,
+    external: Option<FileUrl>,
+    file: Option<FileUrl>,
+}
+
+/// Converts rich text into the `[text, formatting]` form the private API uses.
+pub fn convert_rich_text(rich_text: Vec<RichText>) -> Vec<FormattedText<'static>> {
+    rich_text
+        .into_iter()
+        .map(|text| {
+            let annotations = &text.annotations;
+            let mut formatting = vec![];
+            let styles = [
+                (annotations.bold, NoContextFormat::Bold),
+                (annotations.italic, NoContextFormat::Italic),
+                (annotations.strikethrough, NoContextFormat::Strike),
+                (annotations.underline, NoContextFormat::Underline),
+                (annotations.code, NoContextFormat::Code),
+            ];
+            for (enabled, format) in styles.iter() {
+                if *enabled {
+                    formatting.push(FormatType::NoContext(format.clone()));
+                }
+            }
+            if annotations.color != "default" {
+                let color = from_color(&annotations.color);
+                formatting.push(FormatType::Context(ContextFormat::Highlight(color)));
+            }
+            if let Some(href) = text.href {
+                formatting.push(FormatType::Context(ContextFormat::Link(Cow::Owned(href))));
+            }
+
+            FormattedText {
+                text: Cow::Owned(text.plain_text),
+                formatting: if formatting.is_empty() {
+                    None
+                } else {
+                    Some(formatting)
+                },
+            }
+        })
+        .collect()
+}
+
+/// Converts an ISO 8601 timestamp (eg. `2022-03-01T19:05:00.000Z`) into milliseconds since the
+/// Unix epoch, like the private API's timestamps. Returns 0 if the timestamp can't be read.
+fn parse_time(time: &str) -> i64 {
+    let number = |range: std::ops::Range<usize>| time.get(range).and_then(|s| s.parse::<i64>().ok());
+    let parts = (number(0..4), number(5..7), number(8..10), number(11..13), number(14..16), number(17..19));
+    let (year, month, day, hour, minute, second) = match parts {
+        (Some(y), Some(mo), Some(d), Some(h), Some(mi), Some(s)) => (y, mo, d, h, mi, s),
+        _ => return 0,
+    };
+    let millis = number(20..23).unwrap_or(0);
+
+    // Days since the epoch, from Howard Hinnant's `days_from_civil`.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    ((days * 24 + hour) * 60 + minute) * 60_000 + second * 1000 + millis
+}
+
+fn text_properties(rich_text: Vec<RichText>) -> Option<TextProperties<'static>> {
+    if rich_text.is_empty() {
+        None
+    } else {
+        Some(TextProperties {
+            title: convert_rich_text(rich_text),
+            extra: Map::new(),
+        })
+    }
+}
+
+/// Converts a block's type-specific part into a `RootBlockType`, and returns its color.
+/// `columns` is the number of columns next to the block, to size columns evenly.
+fn convert_block(block_type: &str, mut content: HashMap<String, Value>, columns: usize) -> (RootBlockType<'static>, Option<ColorType>) {
+    let raw = content.remove(block_type).unwrap_or(Value::Null);
+    let text = || TextContent::deserialize(&raw).unwrap_or_default();
+    let color = |text: &TextContent| text.color.as_deref().filter(|c| *c != "default").map(from_color);
+
+    let converted = match block_type {
+        "paragraph" | "heading_1" | "heading_2" | "heading_3" | "bulleted_list_item"
+        | "numbered_list_item" | "toggle" | "quote" => {
+            let text = text();
+            let color = color(&text);
+            let properties = text_properties(text.rich_text);
+            let block = match block_type {
+                "paragraph" => RootBlockType::Text { properties },
+                "heading_1" => RootBlockType::Header { properties },
+                "heading_2" => RootBlockType::SubHeader { properties },
+                "heading_3" => RootBlockType::SubSubHeader { properties },
+                "bulleted_list_item" => RootBlockType::BulletedList { properties },
+                "numbered_list_item" => RootBlockType::NumberedList { properties },
+                "toggle" => RootBlockType::Toggle { properties },
+                _ => RootBlockType::Quote { properties },
+            };
+            Some((block, color))
+        }
+        "to_do" => {
+            let text = text();
+            let color = color(&text);
+            let checked = if text.checked.unwrap_or(false) { YesOrNo::Yes } else { YesOrNo::No };
+            let title = serde_json::to_value(convert_rich_text(text.rich_text)).unwrap_or_default();
+            let properties = Some(TodoProperties {
+                title,
+                checked: vec![vec![checked]],
+            });
+            Some((RootBlockType::ToDo { properties }, color))
+        }
+        "divider" => Some((RootBlockType::Divider, None)),
+        "column_list" => Some((RootBlockType::ColumnList, None)),
+        "column" => {
+            let format = ColumnFormat {
+                column_ratio: 1.0 / columns.max(1) as f64,
+            };
+            Some((RootBlockType::Column { format }, None))
+        }
+        "image" => ImageContent::deserialize(&raw).ok().and_then(|image| {
+            let url = image.external.or(image.file)?.url;
+            let caption = image
+                .caption
+                .into_iter()
+                .map(|text| vec![text.plain_text])
+                .collect::<Vec<_>>();
+            let block = RootBlockType::Image {
+                properties: ImageProperties {
+                    source: vec![vec![url]],
+                    caption: if caption.is_empty() { None } else { Some(caption) },
+                },
+                // The API doesn't return an image's size.
+                format: ImageFormat {
+                    block_width: 0,
+                    block_height: 0,
+                    display_source: None,
+                    block_full_width: None,
+                    block_page_width: None,
+                    block_aspect_ratio: 0.0,
+                    block_preserve_scale: false,
+                },
+                file_ids: vec![],
+            };
+            Some((block, None))
+        }),
+        "child_page" => {
+            let title = raw.get("title").and_then(Value::as_str).unwrap_or("");
+            let properties = PageProperties {
+                title: vec![FormattedText {
+                    text: Cow::Owned(title.to_string()),
+                    formatting: None,
+                }],
+            };
+            let page = RootBlockType::Page {
+                format: None,
+                file_ids: None,
+                permissions: None,
+                properties,
+            };
+            Some((page, None))
+        }
+        _ => None,
+    };
+
+    converted.unwrap_or_else(|| {
+        // Keep blocks of other types (and ones that didn't match) in their `/v1` form.
+        let mut raw_block = serde_json::Map::new();
+        raw_block.insert(String::from("type"), Value::String(block_type.to_string()));
+        raw_block.insert(block_type.to_string(), raw);
+        let block = RootBlockType::Unknown {
+            type_name: block_type.to_string(),
+            raw: Value::Object(raw_block),
+        };
+        (block, None)
+    })
+}
+
+/// Builds a block table out of responses from the official API.
+///
+/// Add the page with `add_page`, then the children of the page and of every block that
+/// `has_children` with `add_children`, in any order.
+#[derive(Default)]
+pub struct BlockTableBuilder {
+    blocks: BlockTableType<'static>,
+    /// The IDs of each block's children, in order. The API returns these separately from the
+    /// blocks themselves, so they are only added to the blocks in `build`.
+    children: HashMap<BlockId, Vec<BlockId>>,
+}
+
+impl BlockTableBuilder {
+    pub fn new() -> Self {
+        BlockTableBuilder::default()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn insert(
+        &mut self,
+        id: BlockId,
+        parent: &Option<Parent>,
+        created_time: &str,
+        last_edited_time: &str,
+        created_by: Option<PartialUser>,
+        last_edited_by: Option<PartialUser>,
+        archived: bool,
+        block: RootBlockType<'static>,
+        block_color: Option<ColorType>,
+    ) {
+        // The workspace doesn't have an ID in the official API.
+        let (parent_id, parent_table) = match parent {
+            Some(Parent::PageId { page_id }) => (*page_id, "block"),
+            Some(Parent::BlockId { block_id }) => (*block_id, "block"),
+            Some(Parent::DatabaseId { database_id }) => (*database_id, "collection"),
+            _ => (BlockId::from_bytes([0; 16]), "space"),
+        };
+        let user = |user: Option<PartialUser>| Cow::Owned(user.map(|u| u.id).unwrap_or_default());
+
+        let value = BaseValueType {
+            id,
+            version: 0,
+            created_time: parse_time(created_time),
+            last_edited_time: parse_time(last_edited_time),
+            parent_id,
+            parent_table: Cow::Borrowed(parent_table),
+            alive: !archived,
+            created_by_table: Cow::Borrowed("notion_user"),
+            created_by_id: user(created_by),
+            last_edited_by_table: Cow::Borrowed("notion_user"),
+            last_edited_by_id: user(last_edited_by),
+            shard_id: None,
+            space_id: None,
+            content: None,
+            format: block_color.map(|color| BlockFormatType {
+                block_color: Some(color),
+                extra: Map::new(),
+            }),
+            block,
+            extra: Map::new(),
+        };
+        self.blocks.insert(
+            id,
+            BlockType {
+                role: Cow::Borrowed("reader"),
+                value: Either::Left(value),
+                parse_error: None,
+            },
+        );
+    }
+
+    /// Adds a page from `/v1/pages/{id}`.
+    pub fn add_page(&mut self, page: Page) {
+        let title = page
+            .properties
+            .values()
+            .find(|property| property.get("type").and_then(Value::as_str) == Some("title"))
+            .and_then(|property| property.get("title"))
+            .and_then(|title| Vec::<RichText>::deserialize(title).ok())
+            .unwrap_or_default();
+        let block = RootBlockType::Page {
+            format: None,
+            file_ids: None,
+            permissions: None,
+            properties: PageProperties {
+                title: convert_rich_text(title),
+            },
+        };
+
+        let parent = Some(page.parent);
+        self.insert(
+            page.id,
+            &parent,
+            &page.created_time,
+            &page.last_edited_time,
+            page.created_by,
+            page.last_edited_by,
+            page.archived,
+            block,
+            None,
+        );
+    }
+
+    /// Adds children from `/v1/blocks/{parent}/children`, after any that were already added.
+    pub fn add_children(&mut self, parent: &BlockId, children: Vec<Block>) {
+        let columns = children.iter().filter(|child| child.block_type == "column").count();
+        let ids = self.children.entry(*parent).or_default();
+        ids.extend(children.iter().map(|child| child.id));
+
+        for child in children {
+            let (block, color) = convert_block(&child.block_type, child.content, columns);
+            self.insert(
+                child.id,
+                &child.parent,
+                &child.created_time,
+                &child.last_edited_time,
+                child.created_by,
+                child.last_edited_by,
+                child.archived,
+                block,
+                color,
+            );
+        }
+    }
+
+    /// Returns the block table. Children whose parent was never added are left out.
+    pub fn build(mut self) -> BlockTableType<'static> {
+        for (parent, children) in self.children {
+            if let Some(BlockType {
+                value: Either::Left(parent),
+                ..
+            }) = self.blocks.get_mut(&parent)
+            {
+                parent.content = Some(children);
+            }
+        }
+        self.blocks
+    }
+}
+
+async fn get<T: DeserializeOwned>(client: &reqwest::Client, token: &str, url: &str) -> Result<T> {
+    let res = client
+        .get(url)
+        .bearer_auth(token)
+        .header("Notion-Version", NOTION_VERSION)
+        .send().await?
+        .error_for_status()?
+        .bytes().await?;
+
+    Ok(serde_json::from_slice(&res)?)
+}
+
+/// Loads a page and every block in it (including the blocks in sub-pages) with an integration
+/// token, which needs to have been given access to the page.
+pub async fn load(token: &str, page_id: &BlockId) -> Result<BlockTableType<'static>> {
+    let client = reqwest::Client::new();
+    let mut builder = BlockTableBuilder::new();
+
+    let page: Page = get(&client, token, &format!("https://api.notion.com/v1/pages/{}", page_id)).await?;
+    builder.add_page(page);
+
+    // The API only returns one level of children at a time, so fetch each block's children in turn.
+    let mut pending = vec![*page_id];
+    while let Some(parent) = pending.pop() {
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut url = format!("https://api.notion.com/v1/blocks/{}/children?page_size=100", parent);
+            if let Some(cursor) = &cursor {
+                url.push_str(&format!("&start_cursor={}", cursor));
+            }
+
+            let list: List<Block> = get(&client, token, &url).await?;
+            pending.extend(list.results.iter().filter(|b| b.has_children).map(|b| b.id));
+            builder.add_children(&parent, list.results);
+
+            match list.next_cursor {
+                Some(next) if list.has_more => cursor = Some(next),
+                _ => break,
+            }
+        }
+    }
+
+    Ok(builder.build())
+}
@@ -0,0 +1,43 @@
+use base::parser::parse_spaces;
+use std::fs;
+
+#[test]
+fn parses_spaces_for_each_user() {
+    let json = fs::read_to_string("fixtures/v3/get_spaces.json").unwrap();
+    let spaces = parse_spaces(json).unwrap();
+    assert_eq!(spaces.len(), 2);
+
+    let user = &spaces["ee5f0f84-409a-440f-983a-a5315961c6e4"];
+    let space = &user.space["f2a1c3d4-5b6e-4f70-8a9b-0c1d2e3f4a5b"].value;
+    assert_eq!(space.name, "Bug bash");
+    assert_eq!(space.icon.as_deref(), Some("🏠"));
+    assert_eq!(space.pages.len(), 2);
+    assert!(space.pages.iter().all(|page| user.block.contains_key(page)));
+    assert_eq!(user.space_view.len(), 1);
+
+    let other_user = &spaces["0c3e9826-b8f7-4f73-927d-2caaf86f1103"];
+    assert_eq!(
+        other_user.notion_user["0c3e9826-b8f7-4f73-927d-2caaf86f1103"]
+            .value
+            .email,
+        "grace@example.com"
+    );
+    assert!(other_user.block.is_empty());
+}
+
+#[test]
+fn parses_spaces_with_missing_and_unknown_fields() {
+    let json = fs::read_to_string("fixtures/v3/get_spaces.json").unwrap();
+    let spaces = parse_spaces(json).unwrap();
+
+    // This space has no icon, domain or pages, and fields (eg. `invite_link_enabled`) the typings
+    // don't know about.
+    let space = &spaces["0c3e9826-b8f7-4f73-927d-2caaf86f1103"].space
+        ["7b8c9d0e-1f2a-4b3c-9d4e-5f6a7b8c9d0e"]
+        .value;
+    assert_eq!(space.name, "Side projects");
+    assert_eq!(space.plan_type.as_deref(), Some("team"));
+    assert!(space.icon.is_none());
+    assert!(space.domain.is_none());
+    assert!(space.pages.is_empty());
+}
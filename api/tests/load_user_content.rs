@@ -0,0 +1,62 @@
+use base::id::BlockId;
+use base::parser::{parse_user_content, Either, RootBlockType};
+use std::fs;
+
+const USER: &str = "ee5f0f84-409a-440f-983a-a5315961c6e4";
+const SPACE: &str = "f2a1c3d4-5b6e-4f70-8a9b-0c1d2e3f4a5b";
+
+fn id(id: &str) -> BlockId {
+    BlockId::parse(id).unwrap()
+}
+
+#[test]
+fn parses_the_users_workspaces_and_pages() {
+    let json = fs::read_to_string("fixtures/v3/load_user_content.json").unwrap();
+    let record_map = parse_user_content(json).unwrap().record_map;
+
+    let user = &record_map.notion_user[USER].value;
+    assert_eq!(user.email, "ada@example.com");
+    assert_eq!(
+        (user.given_name.as_str(), user.family_name.as_str()),
+        ("Ada", "Lovelace")
+    );
+
+    let space = &record_map.space[SPACE].value;
+    assert_eq!(space.name, "Bug bash");
+    assert_eq!(space.domain.as_deref(), Some("bugbash"));
+    assert_eq!(
+        space.pages,
+        [
+            id("59833787-2cf9-4fdf-8782-e53db20768a5"),
+            id("9e4d3c2b-1a0f-4e9d-8c7b-6a5f4e3d2c1b")
+        ]
+    );
+
+    let space_view = &record_map.space_view["a1b2c3d4-e5f6-4a7b-8c9d-0e1f2a3b4c5d"].value;
+    assert_eq!(space_view.space_id, SPACE);
+    assert_eq!(space_view.parent_id, USER);
+    assert_eq!(
+        space_view.bookmarked_pages.as_deref(),
+        Some(&[id("9e4d3c2b-1a0f-4e9d-8c7b-6a5f4e3d2c1b")][..])
+    );
+
+    // Every top-level page is in the block table.
+    assert_eq!(record_map.block.len(), space.pages.len());
+    let page = match &record_map.block[&space.pages[0]].value {
+        Either::Left(value) => value,
+        Either::Right(raw) => panic!("page didn't parse: {}", raw),
+    };
+    assert_eq!(page.space_id.as_deref(), Some(SPACE));
+    match &page.block {
+        RootBlockType::Page {
+            properties, format, ..
+        } => {
+            assert_eq!(properties.title[0].text, "Bug bash");
+            assert_eq!(
+                format.as_ref().and_then(|f| f.page_icon.as_deref()),
+                Some("🐞")
+            );
+        }
+        other => panic!("expected a page, got {:?}", other),
+    }
+}
@@ -0,0 +1,62 @@
+use base::id::BlockId;
+use base::parser::{parse_search, Either, RootBlockType};
+use std::fs;
+
+#[test]
+fn parses_results_and_their_blocks() {
+    let json = fs::read_to_string("fixtures/v3/search.json").unwrap();
+    let data = parse_search(json).unwrap();
+    assert_eq!(data.total, 3);
+
+    let ids: Vec<String> = data
+        .results
+        .iter()
+        .map(|result| result.id.to_string())
+        .collect();
+    assert_eq!(
+        ids,
+        [
+            "acc7eb06-05cd-4603-a384-5e1e4f1f4e72",
+            "9e4d3c2b-1a0f-4e9d-8c7b-6a5f4e3d2c1b",
+            "59833787-2cf9-4fdf-8782-e53db20768a5"
+        ]
+    );
+
+    // Matched terms keep Notion's marker tags.
+    let hit = &data.results[0];
+    assert_eq!(hit.is_navigable, Some(false));
+    assert_eq!(hit.score, Some(71.3468));
+    let highlight = hit.highlight.as_ref().unwrap();
+    assert_eq!(
+        highlight.text.as_deref(),
+        Some("Lacinato <gzkNfoUU>kale</gzkNfoUU> is a variety of kale")
+    );
+    assert_eq!(highlight.path_text.as_deref(), Some("Bug bash"));
+
+    // Page titles can match without a highlighted snippet.
+    assert!(data.results[1].highlight.as_ref().unwrap().text.is_none());
+    assert!(data.results[2].highlight.is_none());
+
+    // Every result, and the page the first one is in, is in the record map.
+    assert!(data
+        .results
+        .iter()
+        .all(|result| data.record_map.block.contains_key(&result.id)));
+    let block = match &data.record_map.block[&hit.id].value {
+        Either::Left(value) => value,
+        Either::Right(raw) => panic!("block didn't parse: {}", raw),
+    };
+    assert_eq!(
+        block.parent_id,
+        BlockId::parse("59833787-2cf9-4fdf-8782-e53db20768a5").unwrap()
+    );
+    match &block.block {
+        RootBlockType::Text {
+            properties: Some(properties),
+        } => assert_eq!(
+            properties.title[0].text,
+            "Lacinato kale is a variety of kale"
+        ),
+        other => panic!("expected text, got {:?}", other),
+    }
+}
@@ -0,0 +1,95 @@
+use api::v1::{Block, BlockTableBuilder, List, Page};
+use base::id::BlockId;
+use base::parser::{BaseValueType, BlockTableType, ColorType, ContextFormat, Either, FormatType, RootBlockType};
+use std::fs;
+
+fn fixture<T: serde::de::DeserializeOwned>(name: &str) -> T {
+    let json = fs::read_to_string(format!("fixtures/v1/{}", name)).unwrap();
+    serde_json::from_str(&json).unwrap()
+}
+
+fn build() -> BlockTableType<'static> {
+    let page_id = BlockId::parse("59833787-2cf9-4fdf-8782-e53db20768a5").unwrap();
+    let toggle_id = BlockId::parse("3f8a2b4c-7d1e-4f5a-9b6c-0d2e4f6a8b1c").unwrap();
+
+    // Children can be added before their parents.
+    let mut builder = BlockTableBuilder::new();
+    builder.add_children(&toggle_id, fixture::<List<Block>>("toggle_children.json").results);
+    builder.add_page(fixture::<Page>("page.json"));
+    builder.add_children(&page_id, fixture::<List<Block>>("children.json").results);
+    builder.build()
+}
+
+fn get<'a>(blocks: &'a BlockTableType<'static>, id: &str) -> &'a BaseValueType<'static> {
+    match &blocks[&BlockId::parse(id).unwrap()].value {
+        Either::Left(value) => value,
+        Either::Right(raw) => panic!("block didn't convert: {}", raw),
+    }
+}
+
+#[test]
+fn converts_pages_and_children_in_order() {
+    let blocks = build();
+    assert_eq!(blocks.len(), 9);
+
+    let page = get(&blocks, "59833787-2cf9-4fdf-8782-e53db20768a5");
+    match &page.block {
+        RootBlockType::Page { properties, .. } => assert_eq!(properties.title[0].text, "Bug bash"),
+        other => panic!("expected a page, got {:?}", other),
+    }
+    assert_eq!(page.parent_table, "space");
+    assert_eq!(page.created_time, 1_646_161_500_000);
+
+    let types: Vec<&str> = page
+        .content
+        .as_ref()
+        .unwrap()
+        .iter()
+        .map(|id| get(&blocks, &id.to_string()).block.type_name())
+        .collect();
+    assert_eq!(types, ["header", "text", "toggle", "to_do", "image", "equation"]);
+
+    let toggle = get(&blocks, "3f8a2b4c-7d1e-4f5a-9b6c-0d2e4f6a8b1c");
+    assert_eq!(toggle.content.as_ref().map(Vec::len), Some(2));
+    assert_eq!(toggle.parent_id, page.id);
+}
+
+#[test]
+fn converts_annotations_into_formatting() {
+    let blocks = build();
+    let paragraph = get(&blocks, "acc7eb06-05cd-4603-a384-5e1e4f1f4e72");
+    assert!(matches!(
+        paragraph.format.as_ref().and_then(|f| f.block_color.as_ref()),
+        Some(ColorType::Blue)
+    ));
+
+    let title = match &paragraph.block {
+        RootBlockType::Text { properties: Some(properties) } => &properties.title,
+        other => panic!("expected text, got {:?}", other),
+    };
+    assert_eq!(title.len(), 4);
+
+    let link = title[0].formatting.as_ref().unwrap();
+    assert!(matches!(link[0], FormatType::NoContext(_)));
+    assert!(matches!(&link[1], FormatType::Context(ContextFormat::Link(url)) if url == "https://en.wikipedia.org/wiki/Lacinato_kale"));
+    assert!(title[1].formatting.is_none());
+    assert!(matches!(
+        &title[2].formatting.as_ref().unwrap()[1],
+        FormatType::Context(ContextFormat::Highlight(ColorType::YellowBackground))
+    ));
+    assert_eq!(title[3].text, "@Avocado");
+}
+
+#[test]
+fn keeps_archived_and_unsupported_blocks() {
+    let blocks = build();
+    assert!(!get(&blocks, "6e5d4c3b-2a1f-4e0d-9c8b-7a6f5e4d3c2b").alive);
+
+    match &get(&blocks, "1a2b3c4d-5e6f-4a8b-9c0d-1e2f3a4b5c6d").block {
+        RootBlockType::Unknown { type_name, raw } => {
+            assert_eq!(type_name, "equation");
+            assert_eq!(raw["equation"]["expression"], "e=mc^2");
+        }
+        other => panic!("expected an unknown block, got {:?}", other),
+    }
+}
@@ -0,0 +1,147 @@
+use crate::diagnostics::{Diagnostic, DiagnosticReason};
+use crate::id::BlockId;
+use crate::parser::{BlockTableType, BlockType, Either};
+use crate::renderer::{BlockRenderer, InlineRenderer, Renderer, WrapperRenderer};
+use anyhow::Result;
+use futures_util::future::BoxFuture;
+use futures_util::stream::{self, StreamExt};
+use std::collections::HashSet;
+
+/// Somewhere to get blocks that aren't in the block table yet, like Notion's API. The `api` crate
+/// implements this with `loadPageChunk`.
+pub trait BlockSource {
+    /// Fetches a block by its ID. The returned table should hold the block if it exists, and can
+    /// hold any number of others (eg. the block's children), which are kept as well.
+    fn fetch<'s>(&'s self, block_id: &'s BlockId) -> BoxFuture<'s, Result<BlockTableType<'static>>>;
+}
+
+/// Renders pages that might not be fully loaded. Before rendering, it walks the page's tree and
+/// fetches every block that is missing from the block table through a `BlockSource`, a level at a
+/// time, with at most `concurrency` fetches running at once.
+///
+/// The templating itself is still done by `Renderer`, so the blocks can also be fetched with
+/// `fetch_missing` and rendered with a `Renderer` that has been set up with a policy or with
+/// deleted blocks included.
+pub struct AsyncRenderer<S> {
+    source: S,
+    /// The most fetches that can be running at once.
+    concurrency: usize,
+    /// The most fetches a single call to `fetch_missing` or `render` can make, if there is a limit.
+    max_fetches: Option<usize>,
+}
+
+impl<S: BlockSource> AsyncRenderer<S> {
+    /// Creates a renderer that fetches from `source`, 4 blocks at a time, with no limit on the
+    /// number of fetches.
+    pub fn new(source: S) -> Self {
+        AsyncRenderer {
+            source,
+            concurrency: 4,
+            max_fetches: None,
+        }
+    }
+
+    /// Sets the most fetches that can be running at once. A limit of 0 is treated as 1.
+    pub fn concurrency(mut self, limit: usize) -> Self {
+        self.concurrency = limit.max(1);
+        self
+    }
+
+    /// Sets the most fetches a single render can make. Blocks that are still missing once the limit
+    /// has been reached are skipped by the renderer like any other missing block.
+    pub fn max_fetches(mut self, limit: usize) -> Self {
+        self.max_fetches = Some(limit);
+        self
+    }
+
+    /// Fetches every block under `block_id` (and the block itself) that is missing from `blocks`,
+    /// adding them to the table. Blocks that are already in the table are never replaced. Returns a
+    /// `DiagnosticReason::FetchFailure` for each fetch that failed.
+    pub async fn fetch_missing(
+        &self,
+        blocks: &mut BlockTableType<'static>,
+        block_id: &BlockId,
+    ) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+        let mut fetches = 0;
+        let mut seen = HashSet::new();
+        seen.insert(*block_id);
+        let mut level = vec![*block_id];
+
+        while !level.is_empty() {
+            let mut missing = level
+                .iter()
+                .filter(|id| !blocks.contains_key(id))
+                .collect::<Vec<_>>();
+            if let Some(max_fetches) = self.max_fetches {
+                missing.truncate(max_fetches.saturating_sub(fetches));
+            }
+            fetches += missing.len();
+
+            let results = stream::iter(missing)
+                .map(|id| async move { (*id, self.source.fetch(id).await) })
+                .buffered(self.concurrency)
+                .collect::<Vec<_>>()
+                .await;
+
+            for (id, result) in results {
+                match result {
+                    Ok(fetched) => {
+                        for (fetched_id, block) in fetched {
+                            blocks.entry(fetched_id).or_insert(block);
+                        }
+                    }
+                    Err(error) => diagnostics.push(Diagnostic {
+                        block_id: id,
+                        block_type: None,
+                        reason: DiagnosticReason::FetchFailure(error.to_string()),
+                    }),
+                }
+            }
+
+            // Move on to the children of this level, skipping any that have been seen already in
+            // case the blocks form a cycle.
+            let mut next_level = vec![];
+            for id in &level {
+                if let Some(BlockType {
+                    value: Either::Left(block_value),
+                    ..
+                }) = blocks.get(id)
+                {
+                    for child_id in block_value.content.iter().flatten() {
+                        if seen.insert(*child_id) {
+                            next_level.push(*child_id);
+                        }
+                    }
+                }
+            }
+            level = next_level;
+        }
+
+        diagnostics
+    }
+
+    /// Fetches the blocks under `block_id` that are missing from `blocks`, then renders it. Returns
+    /// the result along with the diagnostics from both fetching and rendering.
+    pub async fn render<R, B, I, W>(
+        &self,
+        blocks: &mut BlockTableType<'static>,
+        block_id: &BlockId,
+        block_renderer: B,
+        inline_renderer: I,
+        wrapper_renderer: W,
+    ) -> (R, Vec<Diagnostic>)
+    where
+        B: BlockRenderer<R>,
+        I: InlineRenderer<R>,
+        W: WrapperRenderer<R>,
+    {
+        let mut diagnostics = self.fetch_missing(blocks, block_id).await;
+
+        let renderer = Renderer::new(&*blocks, block_renderer, inline_renderer, wrapper_renderer);
+        let result = renderer.render(block_id);
+        diagnostics.extend(renderer.take_diagnostics());
+
+        (result, diagnostics)
+    }
+}
@@ -0,0 +1,64 @@
+use crate::id::BlockId;
+use std::fmt;
+
+/// Why the renderer couldn't fully render a block.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DiagnosticReason {
+    /// The block is in the block table, but doesn't match the parser's typings. Holds the `serde_json` error.
+    ParseFailure(String),
+    /// The block parsed, but the renderer has no `BlockRenderer` hook for its type. Blocks of an
+    /// unknown type (`RootBlockType::Unknown`) are also reported with this reason, even though
+    /// they are passed to `BlockRenderer::unknown_block`.
+    UnsupportedType,
+    /// The block is listed as a child (or was passed to `render`), but isn't in the block table.
+    MissingFromTable,
+    /// The block was missing from the block table, and fetching it from a `BlockSource` failed.
+    /// Holds the error.
+    FetchFailure(String),
+    /// The block has been deleted (`alive: false`), so it and its children were left out. Deleted
+    /// blocks are only rendered when the renderer is set up with `Renderer::include_dead_blocks`.
+    Deleted,
+    /// The current user's role on the block is `none`, so it and its children were left out.
+    NoAccess,
+    /// The renderer's `RenderPolicy` redacted the block, so it and its children were left out.
+    Redacted,
+}
+
+/// A block that the renderer skipped or couldn't fully render, collected while rendering.
+/// Get them from a renderer with `Renderer::take_diagnostics`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub block_id: BlockId,
+    /// The block's Notion type (eg. `callout`), if it could be found.
+    pub block_type: Option<String>,
+    pub reason: DiagnosticReason,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let block_type = self.block_type.as_deref().unwrap_or("unknown type");
+        match &self.reason {
+            DiagnosticReason::ParseFailure(error) => {
+                write!(f, "{} ({}): failed to parse: {}", self.block_id, block_type, error)
+            }
+            DiagnosticReason::UnsupportedType => {
+                write!(f, "{} ({}): unsupported block type", self.block_id, block_type)
+            }
+            DiagnosticReason::MissingFromTable => {
+                write!(f, "{}: missing from the block table", self.block_id)
+            }
+            DiagnosticReason::FetchFailure(error) => {
+                write!(f, "{}: failed to fetch: {}", self.block_id, error)
+            }
+            DiagnosticReason::Deleted => {
+                write!(f, "{} ({}): deleted, skipped", self.block_id, block_type)
+            }
+            DiagnosticReason::NoAccess => {
+                write!(f, "{} ({}): no access, skipped", self.block_id, block_type)
+            }
+            DiagnosticReason::Redacted => {
+                write!(f, "{} ({}): redacted by the render policy", self.block_id, block_type)
+            }
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use anyhow::{anyhow, Result};
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// A validated Notion block ID. IDs are stored as their 16 raw bytes, so they are cheap to copy,
+/// hash and compare no matter which form (case, dashes) they were written in. They are displayed
+/// and serialized in the dashed, lowercase UUID form Notion uses (eg. `ddda599f-ff69-4974-9dec-86f6abf3209a`).
+///
+/// IDs can be parsed from any of the forms Notion hands out:
+/// - Raw 32 character hex IDs (`ddda599fff6949749dec86f6abf3209a`).
+/// - Dashed UUIDs (`ddda599f-ff69-4974-9dec-86f6abf3209a`).
+/// - `notion.so` and `notion.site` URLs, with or without a title slug, query or fragment
+///   (`https://www.notion.so/My-Page-ddda599fff6949749dec86f6abf3209a?pvs=4`).
+/// - Custom domain URLs, as long as the path ends with the page ID.
+///
+/// If a URL has a `p` query parameter (a page opened in Notion's side peek), that page's ID
+/// is used instead of the one in the path.
+///
+/// When deserializing (eg. the keys of the block table), only the raw and dashed forms are accepted.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BlockId([u8; 16]);
+
+fn hex_value(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// Decodes `input` if it is a raw or dashed ID, and returns nothing otherwise.
+fn from_hex(input: &str) -> Option<[u8; 16]> {
+    let input = input.as_bytes();
+    let is_dashed = match input.len() {
+        32 => false,
+        36 => true,
+        _ => return None,
+    };
+    if is_dashed && [8, 13, 18, 23].iter().any(|i| input[*i] != b'-') {
+        return None;
+    }
+
+    let mut digits = input.iter().enumerate().filter_map(|(i, c)| {
+        if is_dashed && matches!(i, 8 | 13 | 18 | 23) {
+            None
+        } else {
+            Some(*c)
+        }
+    });
+
+    let mut bytes = [0; 16];
+    for byte in bytes.iter_mut() {
+        let high = hex_value(digits.next()?)?;
+        let low = hex_value(digits.next()?)?;
+        *byte = high << 4 | low;
+    }
+    Some(bytes)
+}
+
+/// Finds the ID at the end of a URL path segment, eg. the ID in `My-Page-<id>`.
+fn id_from_segment(segment: &str) -> Option<[u8; 16]> {
+    if let Some(bytes) = from_hex(segment) {
+        return Some(bytes);
+    }
+
+    let split = segment.len().checked_sub(32)?;
+    if !segment.is_char_boundary(split) || !segment[..split].ends_with('-') {
+        return None;
+    }
+    from_hex(&segment[split..])
+}
+
+/// Finds the ID in a URL, preferring the `p` query parameter over the last path segment.
+fn id_from_url(url: &str) -> Option<[u8; 16]> {
+    let without_scheme = url.splitn(2, "://").last()?;
+    let without_fragment = without_scheme.split('#').next()?;
+    let mut parts = without_fragment.splitn(2, '?');
+    let path = parts.next()?;
+    let query = parts.next().unwrap_or("");
+
+    let peeked = query
+        .split('&')
+        .filter_map(|pair| pair.strip_prefix("p="))
+        .find_map(id_from_segment);
+    if peeked.is_some() {
+        return peeked;
+    }
+
+    // The first segment is always the host, which never holds the ID.
+    path.split('/')
+        .skip(1)
+        .filter(|segment| !segment.is_empty())
+        .last()
+        .and_then(id_from_segment)
+}
+
+impl BlockId {
+    /// Parses a block ID from a raw ID, a dashed UUID, or a Notion or custom domain URL.
+    /// Returns an error if no ID could be found.
+    pub fn parse(input: &str) -> Result<BlockId> {
+        let input = input.trim();
+        let bytes = from_hex(input).or_else(|| {
+            if input.contains('/') {
+                id_from_url(input)
+            } else {
+                None
+            }
+        });
+
+        match bytes {
+            Some(b) => Ok(BlockId(b)),
+            None => Err(anyhow!("could not find a Notion block ID in `{}`", input)),
+        }
+    }
+
+    /// Creates an ID from its 16 raw bytes.
+    pub fn from_bytes(bytes: [u8; 16]) -> BlockId {
+        BlockId(bytes)
+    }
+
+    /// Returns the 16 raw bytes of the ID.
+    pub fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+}
+
+impl FromStr for BlockId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        BlockId::parse(s)
+    }
+}
+
+impl fmt::Display for BlockId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, byte) in self.0.iter().enumerate() {
+            if matches!(i, 4 | 6 | 8 | 10) {
+                f.write_str("-")?;
+            }
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for BlockId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "BlockId({})", self)
+    }
+}
+
+impl Serialize for BlockId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+struct BlockIdVisitor;
+
+impl<'de> Visitor<'de> for BlockIdVisitor {
+    type Value = BlockId;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a Notion block ID")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<BlockId, E>
+    where
+        E: de::Error,
+    {
+        from_hex(v)
+            .map(BlockId)
+            .ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))
+    }
+}
+
+impl<'de> Deserialize<'de> for BlockId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(BlockIdVisitor)
+    }
+}
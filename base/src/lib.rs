@@ -1,4 +1,10 @@
 // #![warn(missing_docs)]
 
+pub mod async_renderer;
+pub mod diagnostics;
+pub mod id;
+mod lookup;
 pub mod parser;
 pub mod renderer;
+pub mod stream;
+pub mod tree;
@@ -0,0 +1,243 @@
+use crate::diagnostics::{Diagnostic, DiagnosticReason};
+use crate::id::BlockId;
+use crate::parser::*;
+use crate::renderer::{ListPosition, PolicyDecision, RenderPolicy};
+use std::mem;
+use std::sync::Mutex;
+
+/// A child block that is about to be rendered, and where it is among its siblings.
+pub(crate) struct Child<'b> {
+    pub(crate) value: &'b BaseValueType<'b>,
+    pub(crate) index: usize,
+    /// The block's list position, if it is a list item.
+    pub(crate) position: Option<ListPosition>,
+}
+
+/// How a block's rendered children are put together: either on their own, or in a run of list
+/// items of the same kind that are wrapped together. `start` is the index of the run's first child.
+pub(crate) enum Group {
+    Block,
+    List { start: usize, len: usize },
+}
+
+/// Finds blocks in the block table for the renderers, skipping (and reporting) the ones that
+/// shouldn't be rendered, and works out how each block's children are laid out.
+pub(crate) struct BlockLookup<'b> {
+    blocks: &'b BlockTableType<'b>,
+    /// Whether to render blocks that have been deleted (`alive: false`). Off by default.
+    pub(crate) include_dead: bool,
+    /// Decides which blocks are redacted. If there isn't one, every block the user has access to is rendered.
+    pub(crate) policy: Option<Box<dyn RenderPolicy + 'b>>,
+    /// Blocks that were skipped or couldn't be fully rendered since the last call to `take_diagnostics`.
+    diagnostics: Mutex<Vec<Diagnostic>>,
+}
+
+impl<'b> BlockLookup<'b> {
+    pub(crate) fn new(blocks: &'b BlockTableType<'b>) -> Self {
+        BlockLookup {
+            blocks,
+            include_dead: false,
+            policy: None,
+            diagnostics: Mutex::new(vec![]),
+        }
+    }
+
+    pub(crate) fn take_diagnostics(&self) -> Vec<Diagnostic> {
+        mem::take(&mut *self.diagnostics.lock().unwrap())
+    }
+
+    pub(crate) fn report(
+        &self,
+        block_id: &BlockId,
+        block_type: Option<&str>,
+        reason: DiagnosticReason,
+    ) {
+        self.diagnostics.lock().unwrap().push(Diagnostic {
+            block_id: *block_id,
+            block_type: block_type.map(String::from),
+            reason,
+        });
+    }
+
+    /// Finds the permissions of the nearest page a block is in, by following its parents up the block table.
+    fn page_permissions(&self, block_value: &'b BaseValueType<'b>) -> &'b [PagePermissions] {
+        let mut current = block_value;
+        // Bounded by the table's size, in case the parents form a cycle.
+        for _ in 0..=self.blocks.len() {
+            if let RootBlockType::Page {
+                permissions: Some(permissions),
+                ..
+            } = &current.block
+            {
+                return permissions;
+            }
+
+            match self
+                .blocks
+                .get(&current.parent_id)
+                .map(|block| &block.value)
+            {
+                Some(Either::Left(parent)) => current = parent,
+                _ => break,
+            }
+        }
+
+        &[]
+    }
+
+    /// Looks up a block in the block table, reporting a diagnostic if it is missing, failed to parse,
+    /// has been deleted (unless deleted blocks are included), or is left out because of its permissions.
+    pub(crate) fn get_block(&self, block_id: &BlockId) -> Option<&'b BaseValueType<'b>> {
+        let block = self.blocks.get(block_id);
+        match block.map(|block| &block.value) {
+            Some(Either::Left(block_value)) => {
+                let block_type = Some(block_value.block.type_name());
+                let role = block.map(|block| block.role.as_ref()).unwrap_or_default();
+
+                if !block_value.alive && !self.include_dead {
+                    self.report(block_id, block_type, DiagnosticReason::Deleted);
+                    return None;
+                }
+                if role == "none" {
+                    self.report(block_id, block_type, DiagnosticReason::NoAccess);
+                    return None;
+                }
+                if let Some(policy) = &self.policy {
+                    let page_permissions = self.page_permissions(block_value);
+                    if policy.check(block_value, role, page_permissions) == PolicyDecision::Redact {
+                        self.report(block_id, block_type, DiagnosticReason::Redacted);
+                        return None;
+                    }
+                }
+
+                Some(block_value)
+            }
+            Some(Either::Right(raw)) => {
+                let error = block
+                    .and_then(|block| block.parse_error.clone())
+                    .unwrap_or_else(|| String::from("block did not match its typings"));
+                let block_type = raw.get("type").and_then(|t| t.as_str());
+                self.report(block_id, block_type, DiagnosticReason::ParseFailure(error));
+                None
+            }
+            None => {
+                self.report(block_id, None, DiagnosticReason::MissingFromTable);
+                None
+            }
+        }
+    }
+
+    /// This is a helper function for determining if a specific `RootBlockType` needs
+    /// grouping or not. This should probably not be defined on the renderer's struct and should
+    /// probably be moved to the `parser` module.
+    // TODO: Move this to the parser module.
+    fn needs_grouping(&self, value: &RootBlockType) -> bool {
+        matches!(
+            value,
+            RootBlockType::BulletedList { properties: _ }
+                | RootBlockType::NumberedList { properties: _ }
+        )
+    }
+
+    /// Determines if a given `RootBlockType` value can be grouped with the other
+    /// `BaseValueType`s found in the grouping accumulation vector. This only compares the type against
+    /// the first item in the vector; it is assumed that the only items that are allowed to be added to
+    /// the vector are items of the same block type. Therefore, we only need to check a single value,
+    /// instead of checking all values in the vector.
+    ///
+    /// # Arguments
+    /// - `value` - The value to compare against.
+    /// - `vector` - The vector to compare the value against.
+    // TODO: Rewrite the checking logic to use `needs_grouping`.
+    fn can_be_grouped(&self, value: &RootBlockType, vector: &[&BaseValueType]) -> bool {
+        if vector.is_empty() {
+            false
+        } else {
+            let first = &vector[0];
+            matches!(
+                (&first.block, value),
+                (
+                    RootBlockType::BulletedList { properties: _ },
+                    RootBlockType::BulletedList { properties: _ },
+                ) | (
+                    RootBlockType::NumberedList { properties: _ },
+                    RootBlockType::NumberedList { properties: _ },
+                )
+            )
+        }
+    }
+
+    /// Looks up the children of `parent` (or top-level blocks, if there is no parent), working out
+    /// each one's sibling index and list position, and how they are grouped into lists. `visit` is
+    /// called with each child as soon as it is found, so the children can be rendered in order.
+    /// `parent_position` is the parent's list position, which is only used if it is a list item.
+    ///
+    /// If an ID is not found in the block table, it is left out.
+    pub(crate) fn plan_children(
+        &self,
+        block_ids: &[BlockId],
+        parent: Option<&'b BaseValueType<'b>>,
+        parent_position: ListPosition,
+        mut visit: impl FnMut(&Child<'b>),
+    ) -> (Vec<Child<'b>>, Vec<Group>) {
+        let mut children: Vec<Child<'b>> = vec![];
+        let mut groups = vec![];
+        // How many numbered list items there have been, so numbering carries on after an interruption.
+        let mut numbered_items = 0;
+
+        let block_values = block_ids
+            .iter()
+            .filter_map(|block_id| self.get_block(block_id));
+        for (index, block_value) in block_values.enumerate() {
+            let mut position = None;
+
+            if self.needs_grouping(&block_value.block) {
+                // A block that can't join the current run (including a list item of the other kind)
+                // starts a new one.
+                let run_len = match groups.last_mut() {
+                    Some(Group::List { start, len })
+                        if self.can_be_grouped(&block_value.block, &[children[*start].value]) =>
+                    {
+                        *len += 1;
+                        *len
+                    }
+                    _ => {
+                        groups.push(Group::List {
+                            start: index,
+                            len: 1,
+                        });
+                        1
+                    }
+                };
+
+                // Lists nested directly inside of an item of the same kind are a level deeper.
+                let level = match parent {
+                    Some(parent) if self.can_be_grouped(&block_value.block, &[parent]) => {
+                        parent_position.level + 1
+                    }
+                    _ => 0,
+                };
+                let ordinal = match block_value.block {
+                    RootBlockType::NumberedList { properties: _ } => {
+                        numbered_items += 1;
+                        numbered_items
+                    }
+                    _ => run_len,
+                };
+                position = Some(ListPosition { ordinal, level });
+            } else {
+                groups.push(Group::Block);
+            }
+
+            let child = Child {
+                value: block_value,
+                index,
+                position,
+            };
+            visit(&child);
+            children.push(child);
+        }
+
+        (children, groups)
+    }
+}
@@ -1,12 +1,19 @@
+use crate::id::BlockId;
 use anyhow::Result;
-use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use futures_util::stream::{Stream, StreamExt};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::fmt;
+use std::io::Read;
 
+/// Notion writes these capitalized (`[["Yes"]]`), but the lowercase form is accepted too.
 #[derive(Serialize, Deserialize, Debug)]
-#[serde(rename_all = "lowercase")]
 pub enum YesOrNo {
+    #[serde(alias = "yes")]
     Yes,
+    #[serde(alias = "no")]
     No,
 }
 
@@ -23,6 +30,7 @@ pub struct ColumnFormat {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ImageProperties {
     pub source: Vec<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub caption: Option<Vec<Vec<String>>>,
 }
 
@@ -30,8 +38,11 @@ pub struct ImageProperties {
 pub struct ImageFormat {
     pub block_width: i64,
     pub block_height: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub display_source: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub block_full_width: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub block_page_width: Option<bool>,
     pub block_aspect_ratio: f64,
     pub block_preserve_scale: bool,
@@ -39,67 +50,128 @@ pub struct ImageFormat {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct PageFormat {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub page_full_width: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub page_small_text: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub page_cover_position: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub block_locked: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub page_cover: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub page_icon: Option<String>,
 }
 
+/// Who can see a page, and what they can do with it. A page usually has one for each user or
+/// group it is shared with, plus a `public_permission` if it is shared to the web.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct PagePermissions {
     pub role: String,
+    /// The kind of permission, eg. `user_permission` or `public_permission`.
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub permission_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct FigmaProperties {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub source: Option<Value>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct FigmaFormat {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub block_height: Option<i64>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct TextProperties {
-    pub title: Vec<FormattedText>,
+/// The `properties` of a text block. Properties other than `title` are kept in `extra`, so they
+/// survive a round trip.
+#[derive(Serialize, Debug)]
+pub struct TextProperties<'a> {
+    pub title: Vec<FormattedText<'a>>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, Value>,
+}
+
+impl<'a> TextProperties<'a> {
+    pub fn into_owned(self) -> TextProperties<'static> {
+        TextProperties {
+            title: self.title.into_iter().map(FormattedText::into_owned).collect(),
+            extra: self.extra,
+        }
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for TextProperties<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        strict(deserializer, TextPropertiesVisitor)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
-pub struct PageProperties {
-    pub title: Vec<FormattedText>,
+pub struct PageProperties<'a> {
+    #[serde(borrow)]
+    pub title: Vec<FormattedText<'a>>,
+}
+
+impl<'a> PageProperties<'a> {
+    pub fn into_owned(self) -> PageProperties<'static> {
+        PageProperties {
+            title: self.title.into_iter().map(FormattedText::into_owned).collect(),
+        }
+    }
 }
 
+/// The type-specific part of a block, tagged by the block's `type` field.
+///
+/// Block types the parser doesn't know about yet are kept as `Unknown`, holding the type name and
+/// the raw JSON of the block's type-specific fields, so the block's children can still be rendered
+/// and the block serializes back into its original form. Blocks of a known type that don't match
+/// that type's typings still fail to parse.
 #[derive(Serialize, Deserialize, Debug)]
-#[serde(tag = "type", rename_all = "snake_case")]
-pub enum RootBlockType {
+#[serde(remote = "Self", tag = "type", rename_all = "snake_case")]
+pub enum RootBlockType<'a> {
     Text {
-        properties: Option<TextProperties>,
+        #[serde(borrow, skip_serializing_if = "Option::is_none")]
+        properties: Option<TextProperties<'a>>,
     },
     BulletedList {
-        properties: Option<TextProperties>,
+        #[serde(borrow, skip_serializing_if = "Option::is_none")]
+        properties: Option<TextProperties<'a>>,
     },
     NumberedList {
-        properties: Option<TextProperties>,
+        #[serde(borrow, skip_serializing_if = "Option::is_none")]
+        properties: Option<TextProperties<'a>>,
     },
     Toggle {
-        properties: Option<TextProperties>,
+        #[serde(borrow, skip_serializing_if = "Option::is_none")]
+        properties: Option<TextProperties<'a>>,
     },
     Header {
-        properties: Option<TextProperties>,
+        #[serde(borrow, skip_serializing_if = "Option::is_none")]
+        properties: Option<TextProperties<'a>>,
     },
     SubHeader {
-        properties: Option<TextProperties>,
+        #[serde(borrow, skip_serializing_if = "Option::is_none")]
+        properties: Option<TextProperties<'a>>,
     },
     SubSubHeader {
-        properties: Option<TextProperties>,
+        #[serde(borrow, skip_serializing_if = "Option::is_none")]
+        properties: Option<TextProperties<'a>>,
     },
     Quote {
-        properties: Option<TextProperties>,
+        #[serde(borrow, skip_serializing_if = "Option::is_none")]
+        properties: Option<TextProperties<'a>>,
     },
     ToDo {
+        #[serde(skip_serializing_if = "Option::is_none")]
         properties: Option<TodoProperties>,
     },
     Divider,
@@ -113,44 +185,603 @@ pub enum RootBlockType {
         file_ids: Vec<String>,
     },
     Page {
+        #[serde(skip_serializing_if = "Option::is_none")]
         format: Option<PageFormat>,
+        #[serde(skip_serializing_if = "Option::is_none")]
         file_ids: Option<Vec<String>>,
-        properties: PageProperties,
+        #[serde(borrow)]
+        properties: PageProperties<'a>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        permissions: Option<Vec<PagePermissions>>,
     },
     Figma {
+        #[serde(skip_serializing_if = "Option::is_none")]
         properties: Option<FigmaProperties>,
+        #[serde(skip_serializing_if = "Option::is_none")]
         format: Option<FigmaFormat>,
     },
+    #[serde(skip)]
+    Unknown { type_name: String, raw: Value },
+}
+
+/// The `type` names of every block type in `RootBlockType` (except for `Unknown`), along with the
+/// names of the fields each one holds.
+const KNOWN_BLOCK_TYPES: &[(&str, &[&str])] = &[
+    ("text", &["properties"]),
+    ("bulleted_list", &["properties"]),
+    ("numbered_list", &["properties"]),
+    ("toggle", &["properties"]),
+    ("header", &["properties"]),
+    ("sub_header", &["properties"]),
+    ("sub_sub_header", &["properties"]),
+    ("quote", &["properties"]),
+    ("to_do", &["properties"]),
+    ("divider", &[]),
+    ("column_list", &[]),
+    ("column", &["format"]),
+    ("image", &["properties", "format", "file_ids"]),
+    ("page", &["format", "file_ids", "properties", "permissions"]),
+    ("figma", &["properties", "format"]),
+];
+
+/// Returns the names of the fields a known block type holds, or `None` if the type isn't known.
+fn block_fields(type_name: &str) -> Option<&'static [&'static str]> {
+    KNOWN_BLOCK_TYPES
+        .iter()
+        .find(|(name, _)| *name == type_name)
+        .map(|(_, fields)| *fields)
+}
+
+impl<'a> RootBlockType<'a> {
+    /// Returns the block's Notion type name (the `type` field in the JSON, eg. `bulleted_list`).
+    pub fn type_name(&self) -> &str {
+        match self {
+            RootBlockType::Text { .. } => "text",
+            RootBlockType::BulletedList { .. } => "bulleted_list",
+            RootBlockType::NumberedList { .. } => "numbered_list",
+            RootBlockType::Toggle { .. } => "toggle",
+            RootBlockType::Header { .. } => "header",
+            RootBlockType::SubHeader { .. } => "sub_header",
+            RootBlockType::SubSubHeader { .. } => "sub_sub_header",
+            RootBlockType::Quote { .. } => "quote",
+            RootBlockType::ToDo { .. } => "to_do",
+            RootBlockType::Divider => "divider",
+            RootBlockType::ColumnList => "column_list",
+            RootBlockType::Column { .. } => "column",
+            RootBlockType::Image { .. } => "image",
+            RootBlockType::Page { .. } => "page",
+            RootBlockType::Figma { .. } => "figma",
+            RootBlockType::Unknown { type_name, raw: _ } => type_name,
+        }
+    }
+
+    /// Converts the block into one that owns all of its data, copying any strings that were
+    /// borrowed from the parser's input.
+    pub fn into_owned(self) -> RootBlockType<'static> {
+        let text = |properties: Option<TextProperties<'a>>| properties.map(TextProperties::into_owned);
+
+        match self {
+            RootBlockType::Text { properties } => RootBlockType::Text {
+                properties: text(properties),
+            },
+            RootBlockType::BulletedList { properties } => RootBlockType::BulletedList {
+                properties: text(properties),
+            },
+            RootBlockType::NumberedList { properties } => RootBlockType::NumberedList {
+                properties: text(properties),
+            },
+            RootBlockType::Toggle { properties } => RootBlockType::Toggle {
+                properties: text(properties),
+            },
+            RootBlockType::Header { properties } => RootBlockType::Header {
+                properties: text(properties),
+            },
+            RootBlockType::SubHeader { properties } => RootBlockType::SubHeader {
+                properties: text(properties),
+            },
+            RootBlockType::SubSubHeader { properties } => RootBlockType::SubSubHeader {
+                properties: text(properties),
+            },
+            RootBlockType::Quote { properties } => RootBlockType::Quote {
+                properties: text(properties),
+            },
+            RootBlockType::ToDo { properties } => RootBlockType::ToDo { properties },
+            RootBlockType::Divider => RootBlockType::Divider,
+            RootBlockType::ColumnList => RootBlockType::ColumnList,
+            RootBlockType::Column { format } => RootBlockType::Column { format },
+            RootBlockType::Image {
+                properties,
+                format,
+                file_ids,
+            } => RootBlockType::Image {
+                properties,
+                format,
+                file_ids,
+            },
+            RootBlockType::Page {
+                format,
+                file_ids,
+                properties,
+                permissions,
+            } => RootBlockType::Page {
+                format,
+                file_ids,
+                properties: properties.into_owned(),
+                permissions,
+            },
+            RootBlockType::Figma { properties, format } => RootBlockType::Figma { properties, format },
+            RootBlockType::Unknown { type_name, raw } => RootBlockType::Unknown { type_name, raw },
+        }
+    }
+}
+
+impl<'a> Serialize for RootBlockType<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            RootBlockType::Unknown { type_name: _, raw } => raw.serialize(serializer),
+            _ => RootBlockType::serialize(self, serializer),
+        }
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for RootBlockType<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = Value::deserialize(deserializer)?;
+        let type_name = raw.get("type").and_then(Value::as_str).unwrap_or("");
+
+        if block_fields(type_name).is_some() {
+            RootBlockType::deserialize(raw).map_err(de::Error::custom)
+        } else {
+            Ok(RootBlockType::Unknown {
+                type_name: type_name.to_string(),
+                raw,
+            })
+        }
+    }
+}
+
+/// Builds one of the block types that only hold rich text, or returns `None` if `type_name`
+/// isn't one of them.
+fn text_block<'a>(type_name: &str, properties: Option<TextProperties<'a>>) -> Option<RootBlockType<'a>> {
+    match type_name {
+        "text" => Some(RootBlockType::Text { properties }),
+        "bulleted_list" => Some(RootBlockType::BulletedList { properties }),
+        "numbered_list" => Some(RootBlockType::NumberedList { properties }),
+        "toggle" => Some(RootBlockType::Toggle { properties }),
+        "header" => Some(RootBlockType::Header { properties }),
+        "sub_header" => Some(RootBlockType::SubHeader { properties }),
+        "sub_sub_header" => Some(RootBlockType::SubSubHeader { properties }),
+        "quote" => Some(RootBlockType::Quote { properties }),
+        _ => None,
+    }
 }
 
+/// The part of a block's `format` that any block can have. The rest of the format is kept in
+/// `extra` (eg. `block_locked` on a text block), so it survives a round trip even if the block's
+/// type doesn't hold it.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct BlockFormatType {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub block_color: Option<ColorType>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, Value>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct BaseValueType {
-    pub id: String,
+/// A block. The fields every block has are stored here, and the type-specific ones in `block`.
+///
+/// Serializes back into a single JSON object, including the fields kept in `extra`. A block's own `format` (eg. a column's
+/// `column_ratio`) is merged with `format` (which only holds the block color), so both
+/// end up in the same `format` object, like they are in Notion's responses.
+#[derive(Debug)]
+pub struct BaseValueType<'a> {
+    pub id: BlockId,
     pub version: i64,
     pub created_time: i64,
     pub last_edited_time: i64,
-    pub parent_id: String,
-    pub parent_table: String,
+    pub parent_id: BlockId,
+    pub parent_table: Cow<'a, str>,
     pub alive: bool,
-    pub created_by_table: String,
-    pub created_by_id: String,
-    pub last_edited_by_table: String,
-    pub last_edited_by_id: String,
+    pub created_by_table: Cow<'a, str>,
+    pub created_by_id: Cow<'a, str>,
+    pub last_edited_by_table: Cow<'a, str>,
+    pub last_edited_by_id: Cow<'a, str>,
     pub shard_id: Option<i64>,
     pub space_id: Option<String>,
-    pub content: Option<Vec<String>>,
+    pub content: Option<Vec<BlockId>>,
     pub format: Option<BlockFormatType>,
-    #[serde(flatten)]
-    pub block: RootBlockType,
+    pub block: RootBlockType<'a>,
+    /// Fields that neither the other fields nor `block` hold (eg. `copied_from`), kept so they
+    /// survive a round trip.
+    pub extra: serde_json::Map<String, Value>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(rename_all = "snake_case")]
+fn owned(s: Cow<str>) -> Cow<'static, str> {
+    Cow::Owned(s.into_owned())
+}
+
+impl<'a> BaseValueType<'a> {
+    pub fn into_owned(self) -> BaseValueType<'static> {
+        BaseValueType {
+            id: self.id,
+            version: self.version,
+            created_time: self.created_time,
+            last_edited_time: self.last_edited_time,
+            parent_id: self.parent_id,
+            parent_table: owned(self.parent_table),
+            alive: self.alive,
+            created_by_table: owned(self.created_by_table),
+            created_by_id: owned(self.created_by_id),
+            last_edited_by_table: owned(self.last_edited_by_table),
+            last_edited_by_id: owned(self.last_edited_by_id),
+            shard_id: self.shard_id,
+            space_id: self.space_id,
+            content: self.content,
+            format: self.format,
+            block: self.block.into_owned(),
+            extra: self.extra,
+        }
+    }
+}
+
+impl<'a> Serialize for BaseValueType<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use serde::ser::{Error, SerializeMap};
+
+        let mut block = match serde_json::to_value(&self.block).map_err(S::Error::custom)? {
+            Value::Object(block) => block,
+            _ => serde_json::Map::new(),
+        };
+        if let Some(format) = &self.format {
+            let block_format = block
+                .entry("format")
+                .or_insert_with(|| Value::Object(serde_json::Map::new()));
+            if let Value::Object(block_format) = block_format {
+                if let Some(color) = &format.block_color {
+                    let color = serde_json::to_value(color).map_err(S::Error::custom)?;
+                    block_format.insert(String::from("block_color"), color);
+                }
+                // The block's own format wins over the copy of it in `extra`.
+                for (key, value) in &format.extra {
+                    block_format.entry(key).or_insert_with(|| value.clone());
+                }
+            }
+        }
+
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("id", &self.id)?;
+        map.serialize_entry("version", &self.version)?;
+        map.serialize_entry("created_time", &self.created_time)?;
+        map.serialize_entry("last_edited_time", &self.last_edited_time)?;
+        map.serialize_entry("parent_id", &self.parent_id)?;
+        map.serialize_entry("parent_table", &self.parent_table)?;
+        map.serialize_entry("alive", &self.alive)?;
+        map.serialize_entry("created_by_table", &self.created_by_table)?;
+        map.serialize_entry("created_by_id", &self.created_by_id)?;
+        map.serialize_entry("last_edited_by_table", &self.last_edited_by_table)?;
+        map.serialize_entry("last_edited_by_id", &self.last_edited_by_id)?;
+        // Optional fields are left out when they're missing, like they are in Notion's responses.
+        if let Some(shard_id) = &self.shard_id {
+            map.serialize_entry("shard_id", shard_id)?;
+        }
+        if let Some(space_id) = &self.space_id {
+            map.serialize_entry("space_id", space_id)?;
+        }
+        if let Some(content) = &self.content {
+            map.serialize_entry("content", content)?;
+        }
+        for (key, value) in block.iter().chain(&self.extra) {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+/// The fields of a block, collected in a single pass over its JSON object.
+///
+/// The fields every block has are parsed straight into their types. Notion sends a block's
+/// `type` before its type-specific fields, so the `properties` of text blocks are parsed
+/// straight into `TextProperties` too. Any other fields (eg. `format` and `file_ids`, or
+/// `properties` if it came before `type`) are kept as JSON, and turned into a
+/// `RootBlockType` once the whole object has been read.
+///
+/// A field that doesn't have the type it should is kept as JSON in `rest` too, and `invalid` holds
+/// serde's error for it, so one bad value only makes that block fall back to its JSON instead of
+/// failing the parse. The fields are read with `LenientVisitor`s, so nothing is read twice.
+#[derive(Default)]
+struct BlockFields<'a> {
+    id: Option<BlockId>,
+    version: Option<i64>,
+    created_time: Option<i64>,
+    last_edited_time: Option<i64>,
+    parent_id: Option<BlockId>,
+    parent_table: Option<Cow<'a, str>>,
+    alive: Option<bool>,
+    created_by_table: Option<Cow<'a, str>>,
+    created_by_id: Option<Cow<'a, str>>,
+    last_edited_by_table: Option<Cow<'a, str>>,
+    last_edited_by_id: Option<Cow<'a, str>>,
+    shard_id: Option<i64>,
+    space_id: Option<String>,
+    content: Option<Vec<BlockId>>,
+    type_name: Option<Cow<'a, str>>,
+    text_properties: Option<TextProperties<'a>>,
+    rest: serde_json::Map<String, Value>,
+    invalid: Option<String>,
+}
+
+impl<'a> BlockFields<'a> {
+    /// Turns the fields into a block. If they don't match the typings, returns the error
+    /// along with the block's JSON, so the caller can fall back to it.
+    fn build(mut self) -> Result<BaseValueType<'a>, (String, Value)> {
+        if let Some(error) = self.invalid.take() {
+            return Err((error, self.into_raw()));
+        }
+
+        let required = [
+            ("id", self.id.is_some()),
+            ("version", self.version.is_some()),
+            ("created_time", self.created_time.is_some()),
+            ("last_edited_time", self.last_edited_time.is_some()),
+            ("parent_id", self.parent_id.is_some()),
+            ("parent_table", self.parent_table.is_some()),
+            ("alive", self.alive.is_some()),
+            ("created_by_table", self.created_by_table.is_some()),
+            ("created_by_id", self.created_by_id.is_some()),
+            ("last_edited_by_table", self.last_edited_by_table.is_some()),
+            ("last_edited_by_id", self.last_edited_by_id.is_some()),
+        ];
+        if let Some((field, _)) = required.iter().find(|(_, present)| !present) {
+            let error = format!("missing field `{}`", field);
+            return Err((error, self.into_raw()));
+        }
+
+        let format = match self.rest.get("format").map(Option::<BlockFormatType>::deserialize) {
+            Some(Ok(format)) => format,
+            Some(Err(e)) => return Err((e.to_string(), self.into_raw())),
+            None => None,
+        };
+
+        let type_name = self.type_name.take().unwrap_or(Cow::Borrowed(""));
+        let parsed_text_properties = self.text_properties.is_some() || !self.rest.contains_key("properties");
+        let fields = block_fields(&type_name);
+        let block = match (text_block(&type_name, None), fields) {
+            (Some(_), _) if parsed_text_properties => {
+                text_block(&type_name, self.text_properties.take()).unwrap()
+            }
+            (_, Some(fields)) => {
+                // Only the fields the block type holds are passed to it. The rest go in `extra`.
+                let mut raw: serde_json::Map<String, Value> = fields
+                    .iter()
+                    .filter_map(|field| Some((field.to_string(), self.rest.get(*field)?.clone())))
+                    .collect();
+                raw.insert(String::from("type"), Value::String(type_name.to_string()));
+                match RootBlockType::deserialize(Value::Object(raw)) {
+                    Ok(block) => block,
+                    Err(e) => {
+                        self.type_name = Some(type_name);
+                        return Err((e.to_string(), self.into_raw()));
+                    }
+                }
+            }
+            (_, None) => {
+                let mut raw = std::mem::take(&mut self.rest);
+                raw.insert(String::from("type"), Value::String(type_name.to_string()));
+                RootBlockType::Unknown {
+                    type_name: type_name.into_owned(),
+                    raw: Value::Object(raw),
+                }
+            }
+        };
+
+        // `format` is kept in `format`, whether or not the block type holds it too.
+        let fields = fields.unwrap_or_default();
+        let extra = self
+            .rest
+            .into_iter()
+            .filter(|(key, _)| key != "format" && !fields.contains(&key.as_str()))
+            .collect();
+
+        Ok(BaseValueType {
+            id: self.id.unwrap(),
+            version: self.version.unwrap(),
+            created_time: self.created_time.unwrap(),
+            last_edited_time: self.last_edited_time.unwrap(),
+            parent_id: self.parent_id.unwrap(),
+            parent_table: self.parent_table.unwrap(),
+            alive: self.alive.unwrap(),
+            created_by_table: self.created_by_table.unwrap(),
+            created_by_id: self.created_by_id.unwrap(),
+            last_edited_by_table: self.last_edited_by_table.unwrap(),
+            last_edited_by_id: self.last_edited_by_id.unwrap(),
+            shard_id: self.shard_id,
+            space_id: self.space_id,
+            content: self.content,
+            format,
+            block,
+            extra,
+        })
+    }
+
+    /// Puts the fields back together into the block's JSON.
+    fn into_raw(self) -> Value {
+        let mut raw = self.rest;
+        let fields = vec![
+            ("id", to_json(self.id)),
+            ("version", to_json(self.version)),
+            ("type", to_json(self.type_name)),
+            ("created_time", to_json(self.created_time)),
+            ("last_edited_time", to_json(self.last_edited_time)),
+            ("parent_id", to_json(self.parent_id)),
+            ("parent_table", to_json(self.parent_table)),
+            ("alive", to_json(self.alive)),
+            ("created_by_table", to_json(self.created_by_table)),
+            ("created_by_id", to_json(self.created_by_id)),
+            ("last_edited_by_table", to_json(self.last_edited_by_table)),
+            ("last_edited_by_id", to_json(self.last_edited_by_id)),
+            ("shard_id", to_json(self.shard_id)),
+            ("space_id", to_json(self.space_id)),
+            ("content", to_json(self.content)),
+            ("properties", to_json(self.text_properties)),
+        ];
+        for (key, value) in fields {
+            if !value.is_null() {
+                raw.insert(String::from(key), value);
+            }
+        }
+        Value::Object(raw)
+    }
+}
+
+impl<'de> BlockFields<'de> {
+    /// Keeps a field that doesn't have the type it should as JSON, and marks the block as invalid.
+    fn check<T>(&mut self, key: &str, value: Result<T, Invalid>) -> Option<T> {
+        match value {
+            Ok(value) => Some(value),
+            Err(Invalid { raw, error }) => {
+                self.invalid
+                    .get_or_insert_with(|| format!("invalid value for field `{}`: {}", key, error));
+                self.rest.insert(key.to_string(), raw);
+                None
+            }
+        }
+    }
+
+    /// Reads the value of the field `key` (eg. a number or an ID). If it doesn't have the right
+    /// type, keeps it as JSON, marks the block as invalid, and returns `None`.
+    fn next<A, T>(&mut self, map: &mut A, key: &str) -> Result<Option<T>, A::Error>
+    where
+        A: de::MapAccess<'de>,
+        T: de::DeserializeOwned,
+    {
+        let value = map.next_value()?;
+        Ok(self.check(key, parse_scalar(value)))
+    }
+
+    /// Like `next`, but for fields that can be `null`.
+    fn next_nullable<A, T>(&mut self, map: &mut A, key: &str) -> Result<Option<T>, A::Error>
+    where
+        A: de::MapAccess<'de>,
+        T: de::DeserializeOwned,
+    {
+        Ok(match map.next_value()? {
+            JsonValue::Other(Value::Null) => None,
+            value => self.check(key, parse_scalar(value)),
+        })
+    }
+
+    fn next_str<A>(&mut self, map: &mut A, key: &str) -> Result<Option<Cow<'de, str>>, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let value = match map.next_value()? {
+            JsonValue::Str(s) => Ok(s),
+            value => Err(Invalid::of_type(value.into_json(), "a string")),
+        };
+        Ok(self.check(key, value))
+    }
+
+    /// Reads the value of the field `key` with `visitor`.
+    fn next_with<A, V>(&mut self, map: &mut A, key: &str, visitor: V) -> Result<Option<V::Value>, A::Error>
+    where
+        A: de::MapAccess<'de>,
+        V: LenientVisitor<'de>,
+    {
+        let value = map.next_value_seed(Lenient(visitor))?;
+        Ok(self.check(key, value))
+    }
+}
+
+struct BlockFieldsVisitor;
+
+impl<'de> de::Visitor<'de> for BlockFieldsVisitor {
+    type Value = BlockFields<'de>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a block")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let mut fields = BlockFields::default();
+
+        while let Some(CowStr(key)) = map.next_key()? {
+            match key.as_ref() {
+                "id" => fields.id = fields.next(&mut map, &key)?,
+                "version" => fields.version = fields.next(&mut map, &key)?,
+                "created_time" => fields.created_time = fields.next(&mut map, &key)?,
+                "last_edited_time" => fields.last_edited_time = fields.next(&mut map, &key)?,
+                "parent_id" => fields.parent_id = fields.next(&mut map, &key)?,
+                "parent_table" => fields.parent_table = fields.next_str(&mut map, &key)?,
+                "alive" => fields.alive = fields.next(&mut map, &key)?,
+                "created_by_table" => fields.created_by_table = fields.next_str(&mut map, &key)?,
+                "created_by_id" => fields.created_by_id = fields.next_str(&mut map, &key)?,
+                "last_edited_by_table" => fields.last_edited_by_table = fields.next_str(&mut map, &key)?,
+                "last_edited_by_id" => fields.last_edited_by_id = fields.next_str(&mut map, &key)?,
+                "shard_id" => fields.shard_id = fields.next_nullable(&mut map, &key)?,
+                "space_id" => fields.space_id = fields.next_nullable(&mut map, &key)?,
+                "content" => {
+                    fields.content = fields
+                        .next_with(&mut map, &key, Nullable(SeqOf(BlockIdVisitor)))?
+                        .flatten()
+                }
+                "type" => fields.type_name = fields.next_str(&mut map, &key)?,
+                "properties" if fields.type_name.as_deref().and_then(|t| text_block(t, None)).is_some() => {
+                    fields.text_properties = fields
+                        .next_with(&mut map, &key, Nullable(TextPropertiesVisitor))?
+                        .flatten()
+                }
+                _ => {
+                    fields.rest.insert(key.into_owned(), map.next_value()?);
+                }
+            }
+        }
+
+        Ok(fields)
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for BaseValueType<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let fields = deserializer.deserialize_map(BlockFieldsVisitor)?;
+        fields.build().map_err(|(error, _)| de::Error::custom(error))
+    }
+}
+
+/// Parses a block, falling back to its raw JSON (and the error) if it doesn't match the typings.
+/// The block is only read once: the raw JSON is put back together from the fields that were
+/// already parsed.
+#[allow(clippy::type_complexity)]
+fn deserialize_block_value<'de: 'a, 'a, D>(
+    deserializer: D,
+) -> Result<(Either<BaseValueType<'a>, Value>, Option<String>), D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let fields = deserializer.deserialize_map(BlockFieldsVisitor)?;
+    Ok(match fields.build() {
+        Ok(block) => (Either::Left(block), None),
+        Err((error, raw)) => (Either::Right(raw), Some(error)),
+    })
+}
+
+/// A text or background color. Serializes as Notion's name for it (eg. `red_background`). `None` is
+/// Notion's `default` color, and colors the parser doesn't know about are kept in `Unknown` as
+/// they were written, so they survive a round trip.
+#[derive(Debug, Clone)]
 pub enum ColorType {
     Gray,
     Brown,
@@ -171,9 +802,31 @@ pub enum ColorType {
     PinkBackground,
     RedBackground,
     None,
+    Unknown(String),
+}
+
+impl Serialize for ColorType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(to_color(self))
+    }
+}
+
+impl<'de> Deserialize<'de> for ColorType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = CowStr::deserialize(deserializer)?;
+        Ok(from_color(&s.0))
+    }
 }
 
-#[derive(Copy, Clone, Debug)]
+/// A format that doesn't need a second value (eg. bold). Formats the parser doesn't know about are
+/// kept in `Unknown` with their code, so they survive a round trip.
+#[derive(Clone, Debug)]
 pub enum NoContextFormat {
     Bold,
     Italic,
@@ -181,6 +834,21 @@ pub enum NoContextFormat {
     Underline,
     Code,
     None,
+    Unknown(String),
+}
+
+impl NoContextFormat {
+    fn from_code(code: &str) -> Self {
+        match code {
+            "i" => NoContextFormat::Italic,
+            "b" => NoContextFormat::Bold,
+            "s" => NoContextFormat::Strike,
+            "_" => NoContextFormat::Underline,
+            "c" => NoContextFormat::Code,
+            "" => NoContextFormat::None,
+            _ => NoContextFormat::Unknown(code.to_string()),
+        }
+    }
 }
 
 impl Serialize for NoContextFormat {
@@ -188,13 +856,14 @@ impl Serialize for NoContextFormat {
     where
         S: Serializer,
     {
-        serializer.serialize_str(match *self {
+        serializer.serialize_str(match self {
             NoContextFormat::Bold => "b",
             NoContextFormat::Italic => "i",
             NoContextFormat::Strike => "s",
             NoContextFormat::Underline => "_",
             NoContextFormat::Code => "c",
             NoContextFormat::None => "",
+            NoContextFormat::Unknown(code) => code,
         })
     }
 }
@@ -204,166 +873,994 @@ impl<'de> Deserialize<'de> for NoContextFormat {
     where
         D: Deserializer<'de>,
     {
-        let s = String::deserialize(deserializer)?;
-        match s.as_str() {
-            "i" => Ok(NoContextFormat::Italic),
-            "b" => Ok(NoContextFormat::Bold),
-            "s" => Ok(NoContextFormat::Strike),
-            "_" => Ok(NoContextFormat::Underline),
-            "c" => Ok(NoContextFormat::Code),
-            _ => Ok(NoContextFormat::None),
-        }
+        let s = CowStr::deserialize(deserializer)?;
+        Ok(NoContextFormat::from_code(&s.0))
     }
 }
 
-#[derive(Deserialize, Serialize)]
-#[serde(untagged)]
-enum IntermediaryFormatEnum {
-    Main(Either<[NoContextFormat; 1], ContextFormat>),
-}
+/// A string that borrows from the parser's input when it can, and owns its data otherwise
+/// (eg. when it contains escape sequences, or is read from a `Read`). serde only borrows
+/// `Cow<str>` fields marked with `#[serde(borrow)]`, and not ones nested inside of a `Vec`,
+/// `Option` or tuple, so the hand-written deserializers use this instead.
+#[derive(Debug, Clone)]
+pub struct CowStr<'a>(pub Cow<'a, str>);
 
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(untagged)]
-pub enum IntermediaryContextFormattingRepresentation {
-    Main((String, String)),
+impl<'a> Serialize for CowStr<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.0)
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(from = "IntermediaryContextFormattingRepresentation")]
-pub enum ContextFormat {
-    Link(String),
-    Highlight(ColorType),
-    None,
-}
+struct CowStrVisitor;
 
-pub fn from_color(c: &str) -> ColorType {
-    match c {
-        "gray" => ColorType::Gray,
-        "brown" => ColorType::Brown,
-        "orange" => ColorType::Orange,
-        "yellow" => ColorType::Yellow,
-        "teal" => ColorType::Teal,
-        "blue" => ColorType::Blue,
-        "purple" => ColorType::Purple,
-        "pink" => ColorType::Pink,
-        "red" => ColorType::Red,
-        "gray_background" => ColorType::GrayBackground,
-        "brown_background" => ColorType::BrownBackground,
-        "orange_background" => ColorType::OrangeBackground,
-        "yellow_background" => ColorType::YellowBackground,
-        "teal_background" => ColorType::TealBackground,
-        "blue_background" => ColorType::BlueBackground,
-        "purple_background" => ColorType::PurpleBackground,
-        "pink_background" => ColorType::PinkBackground,
-        "red_background" => ColorType::RedBackground,
-        _ => ColorType::None,
+impl<'de> de::Visitor<'de> for CowStrVisitor {
+    type Value = CowStr<'de>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string")
     }
-}
 
-impl From<IntermediaryContextFormattingRepresentation> for ContextFormat {
-    fn from(t: IntermediaryContextFormattingRepresentation) -> Self {
-        let IntermediaryContextFormattingRepresentation::Main((a, b)) = t;
-        match a.as_str() {
-            "a" => ContextFormat::Link(b),
-            "h" => ContextFormat::Highlight(from_color(&b)),
-            _ => ContextFormat::None,
-        }
+    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(CowStr(Cow::Borrowed(v)))
     }
-}
 
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(from = "IntermediaryFormatEnum")]
-pub enum FormatType {
-    NoContext(NoContextFormat),
-    Context(ContextFormat),
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(CowStr(Cow::Owned(v.to_string())))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(CowStr(Cow::Owned(v)))
+    }
 }
 
-impl From<IntermediaryFormatEnum> for FormatType {
-    fn from(t: IntermediaryFormatEnum) -> Self {
-        let IntermediaryFormatEnum::Main(s) = t;
-        match s {
-            Either::Left(l) => FormatType::NoContext(l[0]),
-            Either::Right(l) => FormatType::Context(l),
-        }
+impl<'de: 'a, 'a> Deserialize<'de> for CowStr<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(CowStrVisitor)
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(untagged)]
-enum IntermediaryFormattingRepresentation {
-    Main(Either<Vec<String>, (String, Vec<FormatType>)>),
+/// A JSON value whose strings borrow from the parser's input when they can. Used for values that
+/// are read before their type is checked: the values of formats (links and highlights hold a
+/// string, but other formats, eg. mentions, can hold anything), and the fields of a block.
+enum JsonValue<'a> {
+    Str(Cow<'a, str>),
+    Other(Value),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(from = "IntermediaryFormattingRepresentation")]
-pub struct FormattedText {
-    pub text: String,
-    pub formatting: Option<Vec<FormatType>>,
-}
-
-impl From<IntermediaryFormattingRepresentation> for FormattedText {
-    fn from(text: IntermediaryFormattingRepresentation) -> Self {
-        let IntermediaryFormattingRepresentation::Main(s) = text;
-        match s {
-            Either::Left(r) => FormattedText {
-                text: r[0].clone(),
-                formatting: None,
-            },
-            Either::Right((text, format)) => FormattedText {
-                text,
-                formatting: Some(format),
-            },
+impl<'a> JsonValue<'a> {
+    fn into_json(self) -> Value {
+        match self {
+            JsonValue::Str(s) => Value::String(s.into_owned()),
+            JsonValue::Other(value) => value,
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(untagged)]
-pub enum Either<L, R> {
-    Left(L),
-    Right(R),
+impl<'de: 'a, 'a> Deserialize<'de> for JsonValue<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        strict(deserializer, AnyValue)
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct BlockType {
-    pub role: String,
-    pub value: Either<BaseValueType, Value>,
+/// Turns a value that was already parsed back into JSON.
+fn to_json<T: Serialize>(value: T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct NotionUserValueType {
-    pub id: String,
-    pub version: i64,
-    pub email: String,
-    pub given_name: String,
-    pub family_name: String,
-    pub profile_photo: String,
-    pub pubonboarding_complete: Option<bool>,
-    pub mobile_onboarding_complete: Option<bool>,
-    pub clipper_onboarding_complete: Option<bool>,
+/// A value that doesn't have the shape it should, kept as JSON along with serde's error for it.
+struct Invalid {
+    raw: Value,
+    error: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct NotionUserType {
-    pub role: String,
-    pub value: NotionUserValueType,
-}
+impl Invalid {
+    fn of_type(raw: Value, expected: &str) -> Self {
+        let error = <serde_json::Error as de::Error>::invalid_type(unexpected(&raw), &expected).to_string();
+        Invalid { raw, error }
+    }
 
-pub type BlockTableType = HashMap<String, BlockType>;
+    fn of_length(raw: Vec<Value>, expected: &str) -> Self {
+        let error = <serde_json::Error as de::Error>::invalid_length(raw.len(), &expected).to_string();
+        Invalid {
+            raw: Value::Array(raw),
+            error,
+        }
+    }
+}
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct RecordMapType {
-    pub block: HashMap<String, BlockType>,
-    pub notion_user: HashMap<String, NotionUserType>,
+/// Describes a JSON value for an error message.
+fn unexpected(value: &Value) -> de::Unexpected<'_> {
+    match value {
+        Value::Null => de::Unexpected::Unit,
+        Value::Bool(b) => de::Unexpected::Bool(*b),
+        Value::Number(n) => match (n.as_u64(), n.as_i64()) {
+            (Some(n), _) => de::Unexpected::Unsigned(n),
+            (_, Some(n)) => de::Unexpected::Signed(n),
+            _ => de::Unexpected::Float(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => de::Unexpected::Str(s),
+        Value::Array(_) => de::Unexpected::Seq,
+        Value::Object(_) => de::Unexpected::Map,
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Visits a value that should have a certain shape, but is kept as JSON (`Invalid`) if it doesn't,
+/// so one odd value doesn't fail the whole parse. Unlike an untagged enum, nothing is buffered:
+/// the value is read once, and only the parts that don't fit are turned into JSON. Values are
+/// read with one of these through `Lenient`.
+///
+/// Each hook keeps the value as JSON unless the visitor overrides it.
+trait LenientVisitor<'de>: Sized {
+    type Value;
+
+    /// What the value should be, for error messages (eg. `a string`).
+    fn expecting(&self) -> &'static str;
+
+    /// Visits a string, number, bool or `null`.
+    fn visit_scalar(self, value: JsonValue<'de>) -> Result<Self::Value, Invalid> {
+        Err(Invalid::of_type(value.into_json(), self.expecting()))
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<Result<Self::Value, Invalid>, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let raw = Value::deserialize(de::value::SeqAccessDeserializer::new(seq))?;
+        Ok(Err(Invalid::of_type(raw, self.expecting())))
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Result<Self::Value, Invalid>, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let raw = Value::deserialize(de::value::MapAccessDeserializer::new(map))?;
+        Ok(Err(Invalid::of_type(raw, self.expecting())))
+    }
+}
+
+/// Reads a value with a `LenientVisitor`. Can be used as a `DeserializeSeed`.
+#[derive(Clone, Copy)]
+struct Lenient<V>(V);
+
+impl<'de, V: LenientVisitor<'de>> de::Visitor<'de> for Lenient<V> {
+    type Value = Result<V::Value, Invalid>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.0.expecting())
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(self.0.visit_scalar(JsonValue::Str(Cow::Borrowed(v))))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(self.0.visit_scalar(JsonValue::Str(Cow::Owned(v.to_string()))))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(self.0.visit_scalar(JsonValue::Str(Cow::Owned(v))))
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(self.0.visit_scalar(JsonValue::Other(Value::from(v))))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(self.0.visit_scalar(JsonValue::Other(Value::from(v))))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(self.0.visit_scalar(JsonValue::Other(Value::from(v))))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(self.0.visit_scalar(JsonValue::Other(Value::from(v))))
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(self.0.visit_scalar(JsonValue::Other(Value::Null)))
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        self.0.visit_seq(seq)
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        self.0.visit_map(map)
+    }
+}
+
+impl<'de, V: LenientVisitor<'de>> de::DeserializeSeed<'de> for Lenient<V> {
+    type Value = Result<V::Value, Invalid>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+/// Reads a value with a `LenientVisitor`, failing if it doesn't have the shape it should.
+fn strict<'de, D, V>(deserializer: D, visitor: V) -> Result<V::Value, D::Error>
+where
+    D: Deserializer<'de>,
+    V: LenientVisitor<'de>,
+{
+    deserializer
+        .deserialize_any(Lenient(visitor))?
+        .map_err(|invalid| de::Error::custom(invalid.error))
+}
+
+/// Finishes reading an array that doesn't have the shape it should, keeping it as JSON. `read`
+/// holds the values that were already read from it.
+fn reject_rest<'de, A, T>(mut read: Vec<Value>, mut seq: A, error: String) -> Result<Result<T, Invalid>, A::Error>
+where
+    A: de::SeqAccess<'de>,
+{
+    while let Some(value) = seq.next_element()? {
+        read.push(value);
+    }
+    Ok(Err(Invalid {
+        raw: Value::Array(read),
+        error,
+    }))
+}
+
+/// Parses a string, number, bool or `null` into a `T`, keeping serde's error if it doesn't fit.
+fn parse_scalar<T: de::DeserializeOwned>(value: JsonValue) -> Result<T, Invalid> {
+    let parsed = match &value {
+        JsonValue::Str(s) => T::deserialize(de::value::BorrowedStrDeserializer::<serde_json::Error>::new(s)),
+        JsonValue::Other(raw) => T::deserialize(raw),
+    };
+    parsed.map_err(|e| Invalid {
+        raw: value.into_json(),
+        error: e.to_string(),
+    })
+}
+
+/// Any value.
+#[derive(Clone, Copy)]
+struct AnyValue;
+
+impl<'de> LenientVisitor<'de> for AnyValue {
+    type Value = JsonValue<'de>;
+
+    fn expecting(&self) -> &'static str {
+        "any value"
+    }
+
+    fn visit_scalar(self, value: JsonValue<'de>) -> Result<Self::Value, Invalid> {
+        Ok(value)
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<Result<Self::Value, Invalid>, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let raw = Value::deserialize(de::value::SeqAccessDeserializer::new(seq))?;
+        Ok(Ok(JsonValue::Other(raw)))
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Result<Self::Value, Invalid>, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let raw = Value::deserialize(de::value::MapAccessDeserializer::new(map))?;
+        Ok(Ok(JsonValue::Other(raw)))
+    }
+}
+
+/// `null`, or a value read with `V`.
+#[derive(Clone, Copy)]
+struct Nullable<V>(V);
+
+impl<'de, V: LenientVisitor<'de>> LenientVisitor<'de> for Nullable<V> {
+    type Value = Option<V::Value>;
+
+    fn expecting(&self) -> &'static str {
+        self.0.expecting()
+    }
+
+    fn visit_scalar(self, value: JsonValue<'de>) -> Result<Self::Value, Invalid> {
+        match value {
+            JsonValue::Other(Value::Null) => Ok(None),
+            value => self.0.visit_scalar(value).map(Some),
+        }
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<Result<Self::Value, Invalid>, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        Ok(self.0.visit_seq(seq)?.map(Some))
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Result<Self::Value, Invalid>, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        Ok(self.0.visit_map(map)?.map(Some))
+    }
+}
+
+/// An array of values read with `V`. If one of them doesn't have the shape it should, the whole
+/// array is kept as JSON, with the values that were already read put back into it.
+#[derive(Clone, Copy)]
+struct SeqOf<V>(V);
+
+impl<'de, V> LenientVisitor<'de> for SeqOf<V>
+where
+    V: LenientVisitor<'de> + Copy,
+    V::Value: Serialize,
+{
+    type Value = Vec<V::Value>;
+
+    fn expecting(&self) -> &'static str {
+        "an array"
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Result<Self::Value, Invalid>, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(value) = seq.next_element_seed(Lenient(self.0))? {
+            match value {
+                Ok(value) => values.push(value),
+                Err(invalid) => {
+                    let mut read: Vec<Value> = values.iter().map(to_json).collect();
+                    read.push(invalid.raw);
+                    return reject_rest(read, seq, invalid.error);
+                }
+            }
+        }
+
+        Ok(Ok(values))
+    }
+}
+
+/// A block ID, eg. one of a block's `content`.
+#[derive(Clone, Copy)]
+struct BlockIdVisitor;
+
+impl<'de> LenientVisitor<'de> for BlockIdVisitor {
+    type Value = BlockId;
+
+    fn expecting(&self) -> &'static str {
+        "a Notion block ID"
+    }
+
+    fn visit_scalar(self, value: JsonValue<'de>) -> Result<Self::Value, Invalid> {
+        parse_scalar(value)
+    }
+}
+
+/// The `properties` of a text block.
+#[derive(Clone, Copy)]
+struct TextPropertiesVisitor;
+
+impl<'de> LenientVisitor<'de> for TextPropertiesVisitor {
+    type Value = TextProperties<'de>;
+
+    fn expecting(&self) -> &'static str {
+        "a text block's properties"
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Result<Self::Value, Invalid>, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let mut title = None;
+        let mut error = None;
+        // Everything but a valid title: the other properties, and the title too if it's invalid.
+        let mut raw = serde_json::Map::new();
+
+        while let Some(CowStr(key)) = map.next_key()? {
+            match key.as_ref() {
+                "title" => match map.next_value_seed(Lenient(SeqOf(FormattedTextVisitor)))? {
+                    Ok(value) => title = Some(value),
+                    Err(invalid) => {
+                        error.get_or_insert(invalid.error);
+                        raw.insert(key.into_owned(), invalid.raw);
+                    }
+                },
+                _ => {
+                    raw.insert(key.into_owned(), map.next_value()?);
+                }
+            }
+        }
+
+        match (title, error) {
+            (Some(title), None) => Ok(Ok(TextProperties { title, extra: raw })),
+            (title, error) => {
+                let error = error.unwrap_or_else(|| {
+                    <serde_json::Error as de::Error>::missing_field("title").to_string()
+                });
+                if let Some(title) = title {
+                    raw.insert(String::from("title"), to_json(title));
+                }
+                Ok(Err(Invalid {
+                    raw: Value::Object(raw),
+                    error,
+                }))
+            }
+        }
+    }
+}
+
+/// A format that needs a second value to render (eg. a link needs its URL). Serializes back into
+/// Notion's `["a", "https://..."]` form. Formats the parser doesn't know about (eg. mentions,
+/// dates, and comments) are kept in `Unknown` with their raw value, so they survive a round trip.
+#[derive(Debug, Clone)]
+pub enum ContextFormat<'a> {
+    Link(Cow<'a, str>),
+    Highlight(ColorType),
+    Unknown(String, Value),
+}
+
+impl<'a> ContextFormat<'a> {
+    pub fn into_owned(self) -> ContextFormat<'static> {
+        match self {
+            ContextFormat::Link(link) => ContextFormat::Link(owned(link)),
+            ContextFormat::Highlight(color) => ContextFormat::Highlight(color),
+            ContextFormat::Unknown(a, b) => ContextFormat::Unknown(a, b),
+        }
+    }
+
+    fn new(code: Cow<'a, str>, value: JsonValue<'a>) -> Self {
+        match (code.as_ref(), value) {
+            ("a", JsonValue::Str(link)) => ContextFormat::Link(link),
+            ("h", JsonValue::Str(color)) => ContextFormat::Highlight(from_color(&color)),
+            (_, JsonValue::Str(value)) => ContextFormat::Unknown(code.into_owned(), Value::String(value.into_owned())),
+            (_, JsonValue::Other(value)) => ContextFormat::Unknown(code.into_owned(), value),
+        }
+    }
+}
+
+impl<'a> Serialize for ContextFormat<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            ContextFormat::Link(link) => ("a", link).serialize(serializer),
+            ContextFormat::Highlight(color) => ("h", to_color(color)).serialize(serializer),
+            ContextFormat::Unknown(code, value) => (code, value).serialize(serializer),
+        }
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for ContextFormat<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match FormatType::deserialize(deserializer)? {
+            FormatType::Context(format) => Ok(format),
+            FormatType::NoContext(_) => Err(de::Error::invalid_length(1, &"a format and its value")),
+        }
+    }
+}
+
+pub fn from_color(c: &str) -> ColorType {
+    match c {
+        "gray" => ColorType::Gray,
+        "brown" => ColorType::Brown,
+        "orange" => ColorType::Orange,
+        "yellow" => ColorType::Yellow,
+        "teal" => ColorType::Teal,
+        "blue" => ColorType::Blue,
+        "purple" => ColorType::Purple,
+        "pink" => ColorType::Pink,
+        "red" => ColorType::Red,
+        "gray_background" => ColorType::GrayBackground,
+        "brown_background" => ColorType::BrownBackground,
+        "orange_background" => ColorType::OrangeBackground,
+        "yellow_background" => ColorType::YellowBackground,
+        "teal_background" => ColorType::TealBackground,
+        "blue_background" => ColorType::BlueBackground,
+        "purple_background" => ColorType::PurpleBackground,
+        "pink_background" => ColorType::PinkBackground,
+        "red_background" => ColorType::RedBackground,
+        "default" => ColorType::None,
+        _ => ColorType::Unknown(c.to_string()),
+    }
+}
+
+/// The reverse of `from_color`. `ColorType::None` becomes Notion's `default` color.
+pub fn to_color(c: &ColorType) -> &str {
+    match c {
+        ColorType::Gray => "gray",
+        ColorType::Brown => "brown",
+        ColorType::Orange => "orange",
+        ColorType::Yellow => "yellow",
+        ColorType::Teal => "teal",
+        ColorType::Blue => "blue",
+        ColorType::Purple => "purple",
+        ColorType::Pink => "pink",
+        ColorType::Red => "red",
+        ColorType::GrayBackground => "gray_background",
+        ColorType::BrownBackground => "brown_background",
+        ColorType::OrangeBackground => "orange_background",
+        ColorType::YellowBackground => "yellow_background",
+        ColorType::TealBackground => "teal_background",
+        ColorType::BlueBackground => "blue_background",
+        ColorType::PurpleBackground => "purple_background",
+        ColorType::PinkBackground => "pink_background",
+        ColorType::RedBackground => "red_background",
+        ColorType::None => "default",
+        ColorType::Unknown(color) => color,
+    }
+}
+
+/// A single piece of formatting on a `FormattedText`. Serializes back into Notion's `["b"]`
+/// or `["a", "https://..."]` form.
+#[derive(Debug, Clone)]
+pub enum FormatType<'a> {
+    NoContext(NoContextFormat),
+    Context(ContextFormat<'a>),
+}
+
+impl<'a> FormatType<'a> {
+    pub fn into_owned(self) -> FormatType<'static> {
+        match self {
+            FormatType::NoContext(format) => FormatType::NoContext(format),
+            FormatType::Context(format) => FormatType::Context(format.into_owned()),
+        }
+    }
+}
+
+impl<'a> Serialize for FormatType<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            FormatType::NoContext(format) => [format].serialize(serializer),
+            FormatType::Context(format) => format.serialize(serializer),
+        }
+    }
+}
+
+/// A format: `["b"]`, or `["a", "https://..."]` for one that takes a value.
+#[derive(Clone, Copy)]
+struct FormatTypeVisitor;
+
+impl<'de> LenientVisitor<'de> for FormatTypeVisitor {
+    type Value = FormatType<'de>;
+
+    fn expecting(&self) -> &'static str {
+        "an array holding a format and, optionally, its value"
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Result<Self::Value, Invalid>, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let code = match seq.next_element()? {
+            Some(JsonValue::Str(code)) => code,
+            Some(value) => {
+                let invalid = Invalid::of_type(value.into_json(), "a string");
+                return reject_rest(vec![invalid.raw], seq, invalid.error);
+            }
+            None => return Ok(Err(Invalid::of_length(vec![], self.expecting()))),
+        };
+        let format = match seq.next_element()? {
+            Some(value) => FormatType::Context(ContextFormat::new(code, value)),
+            None => FormatType::NoContext(NoContextFormat::from_code(&code)),
+        };
+        while seq.next_element::<de::IgnoredAny>()?.is_some() {}
+
+        Ok(Ok(format))
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for FormatType<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        strict(deserializer, FormatTypeVisitor)
+    }
+}
+
+/// A run of text and its formatting. Serializes back into Notion's `["text"]` or
+/// `["text", [["b"], ["a", "https://..."]]]` form.
+#[derive(Debug, Clone)]
+pub struct FormattedText<'a> {
+    pub text: Cow<'a, str>,
+    pub formatting: Option<Vec<FormatType<'a>>>,
+}
+
+impl<'a> FormattedText<'a> {
+    pub fn into_owned(self) -> FormattedText<'static> {
+        FormattedText {
+            text: owned(self.text),
+            formatting: self
+                .formatting
+                .map(|f| f.into_iter().map(FormatType::into_owned).collect()),
+        }
+    }
+}
+
+impl<'a> Serialize for FormattedText<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match &self.formatting {
+            Some(formatting) => (&self.text, formatting).serialize(serializer),
+            None => [&self.text].serialize(serializer),
+        }
+    }
+}
+
+/// A run of text: `["text"]`, or `["text", [["b"], ...]]` if it is formatted.
+#[derive(Clone, Copy)]
+struct FormattedTextVisitor;
+
+impl<'de> LenientVisitor<'de> for FormattedTextVisitor {
+    type Value = FormattedText<'de>;
+
+    fn expecting(&self) -> &'static str {
+        "an array holding a string and, optionally, its formatting"
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Result<Self::Value, Invalid>, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let text = match seq.next_element()? {
+            Some(JsonValue::Str(text)) => text,
+            Some(value) => {
+                let invalid = Invalid::of_type(value.into_json(), "a string");
+                return reject_rest(vec![invalid.raw], seq, invalid.error);
+            }
+            None => return Ok(Err(Invalid::of_length(vec![], self.expecting()))),
+        };
+        let formatting = match seq.next_element_seed(Lenient(Nullable(SeqOf(FormatTypeVisitor))))? {
+            Some(Ok(formatting)) => formatting,
+            Some(Err(invalid)) => {
+                let read = vec![Value::String(text.into_owned()), invalid.raw];
+                return reject_rest(read, seq, invalid.error);
+            }
+            None => None,
+        };
+        while seq.next_element::<de::IgnoredAny>()?.is_some() {}
+
+        Ok(Ok(FormattedText { text, formatting }))
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for FormattedText<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        strict(deserializer, FormattedTextVisitor)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+/// A block and the current user's role on it. Blocks that don't match the typings are kept in
+/// `value` as raw JSON, so one odd block doesn't stop the rest of the page from parsing.
+#[derive(Serialize, Debug)]
+pub struct BlockType<'a> {
+    pub role: Cow<'a, str>,
+    pub value: Either<BaseValueType<'a>, Value>,
+    /// Why `value` is raw JSON, if it is: the error from parsing it. Not serialized.
+    #[serde(skip)]
+    pub parse_error: Option<String>,
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for BlockType<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct Fields<'a> {
+            #[serde(borrow)]
+            role: Cow<'a, str>,
+            #[serde(borrow, deserialize_with = "deserialize_block_value")]
+            value: (Either<BaseValueType<'a>, Value>, Option<String>),
+        }
+
+        let Fields {
+            role,
+            value: (value, parse_error),
+        } = Fields::deserialize(deserializer)?;
+        Ok(BlockType {
+            role,
+            value,
+            parse_error,
+        })
+    }
+}
+
+impl<'a> BlockType<'a> {
+    pub fn into_owned(self) -> BlockType<'static> {
+        BlockType {
+            role: owned(self.role),
+            value: match self.value {
+                Either::Left(value) => Either::Left(value.into_owned()),
+                Either::Right(raw) => Either::Right(raw),
+            },
+            parse_error: self.parse_error,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NotionUserValueType {
+    pub id: String,
+    pub version: i64,
+    pub email: String,
+    pub given_name: String,
+    pub family_name: String,
+    pub profile_photo: String,
+    pub pubonboarding_complete: Option<bool>,
+    pub mobile_onboarding_complete: Option<bool>,
+    pub clipper_onboarding_complete: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NotionUserType {
+    pub role: String,
+    pub value: NotionUserValueType,
+}
+
+/// A workspace. `pages` holds the IDs of the workspace's top-level pages, in sidebar order.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SpaceValue {
+    pub id: String,
+    pub version: i64,
+    pub name: String,
+    pub icon: Option<String>,
+    pub domain: Option<String>,
+    pub plan_type: Option<String>,
+    #[serde(default)]
+    pub pages: Vec<BlockId>,
+    pub created_time: Option<i64>,
+    pub created_by_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SpaceType {
+    pub role: String,
+    pub value: SpaceValue,
+}
+
+/// A user's view of a workspace they belong to, holding their own bookmarks and shared pages.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SpaceViewValue {
+    pub id: String,
+    pub version: i64,
+    pub space_id: String,
+    pub parent_id: String,
+    pub parent_table: String,
+    pub alive: bool,
+    pub joined: Option<bool>,
+    pub bookmarked_pages: Option<Vec<BlockId>>,
+    pub shared_pages: Option<Vec<BlockId>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SpaceViewType {
+    pub role: String,
+    pub value: SpaceViewValue,
+}
+
+pub type BlockTableType<'a> = HashMap<BlockId, BlockType<'a>>;
+
+#[derive(Serialize, Deserialize, Debug)]
+/// The records returned by most endpoints, grouped by table.
+///
+/// Parsed record maps borrow strings (eg. block text) from the parser's input where they can.
+/// Use `into_owned` to detach a record map from its input, eg. to cache it.
+pub struct RecordMapType<'a> {
+    #[serde(borrow, default, skip_serializing_if = "HashMap::is_empty")]
+    pub block: BlockTableType<'a>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub notion_user: HashMap<String, NotionUserType>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub space: HashMap<String, SpaceType>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub space_view: HashMap<String, SpaceViewType>,
+}
+
+impl<'a> RecordMapType<'a> {
+    pub fn into_owned(self) -> RecordMapType<'static> {
+        RecordMapType {
+            block: self
+                .block
+                .into_iter()
+                .map(|(id, block)| (id, block.into_owned()))
+                .collect(),
+            notion_user: self.notion_user,
+            space: self.space,
+            space_view: self.space_view,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct LoadPageChunkData {
-    pub record_map: RecordMapType,
+pub struct LoadPageChunkData<'a> {
+    #[serde(borrow)]
+    pub record_map: RecordMapType<'a>,
+}
+
+/// Parses a `loadPageChunk` response, borrowing text, URLs and other strings from `input`
+/// instead of allocating them. Strings that contain escape sequences still need to be
+/// unescaped, so those are allocated.
+pub fn parse<'a>(input: &'a str) -> Result<LoadPageChunkData<'a>> {
+    let result: LoadPageChunkData<'a> = serde_json::from_str(input)?;
+    Ok(result)
+}
+
+/// Parses a `loadPageChunk` response into a value that owns all of its data, for when
+/// the result needs to outlive `input`.
+pub fn parse_owned(input: String) -> Result<LoadPageChunkData<'static>> {
+    let result = parse(&input)?;
+    Ok(LoadPageChunkData {
+        record_map: result.record_map.into_owned(),
+    })
+}
+
+/// Parses a `loadPageChunk` response from bytes (eg. an HTTP response body), borrowing strings
+/// from `input` like `parse` does. The bytes are checked for UTF-8 as they're parsed.
+pub fn parse_slice<'a>(input: &'a [u8]) -> Result<LoadPageChunkData<'a>> {
+    let result: LoadPageChunkData<'a> = serde_json::from_slice(input)?;
+    Ok(result)
 }
 
-pub fn parse(input: String) -> Result<LoadPageChunkData> {
-    let result: LoadPageChunkData = serde_json::from_str(&input)?;
+/// Parses a `loadPageChunk` response from a reader (eg. a file), without reading all of it into
+/// a `String` first. serde_json reads one byte at a time, so wrap files and sockets in a
+/// `BufReader`. Nothing can be borrowed from a reader, so the result owns all of its data.
+pub fn parse_reader<R: Read>(reader: R) -> Result<LoadPageChunkData<'static>> {
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    let result = LoadPageChunkData::deserialize(&mut deserializer)?;
+    deserializer.end()?;
     Ok(result)
 }
+
+/// Parses a `loadPageChunk` response from a stream of byte chunks, eg. an HTTP response body.
+///
+/// This doesn't parse as the chunks arrive: serde_json can't pause in the middle of a value to
+/// wait for more input, so the chunks are joined into one buffer, which is parsed once the stream
+/// ends. While it's parsed, the whole response and the blocks parsed from it are in memory at
+/// once. When the response is already in one buffer, `parse_slice` skips the copy.
+pub async fn parse_stream<S, B, E>(stream: S) -> Result<LoadPageChunkData<'static>>
+where
+    S: Stream<Item = Result<B, E>>,
+    B: AsRef<[u8]>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    futures_util::pin_mut!(stream);
+    let mut body = vec![];
+    while let Some(chunk) = stream.next().await {
+        body.extend_from_slice(chunk?.as_ref());
+    }
+
+    let result = parse_slice(&body)?;
+    Ok(LoadPageChunkData {
+        record_map: result.record_map.into_owned(),
+    })
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadUserContentData<'a> {
+    #[serde(borrow)]
+    pub record_map: RecordMapType<'a>,
+}
+
+pub fn parse_user_content(input: String) -> Result<LoadUserContentData<'static>> {
+    let result: LoadUserContentData = serde_json::from_str(&input)?;
+    Ok(LoadUserContentData {
+        record_map: result.record_map.into_owned(),
+    })
+}
+
+/// The response of `getSpaces`: a record map for each user the token is logged in as, keyed by user ID.
+pub type GetSpacesData<'a> = HashMap<String, RecordMapType<'a>>;
+
+pub fn parse_spaces(input: String) -> Result<GetSpacesData<'static>> {
+    let result: GetSpacesData = serde_json::from_str(&input)?;
+    Ok(result
+        .into_iter()
+        .map(|(user_id, record_map)| (user_id, record_map.into_owned()))
+        .collect())
+}
+
+/// The snippet Notion returns for a search hit. Matched terms are wrapped in Notion's own
+/// marker tags (eg. `<gzkNfoUU>term</gzkNfoUU>`), which are left as-is.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHighlightType {
+    pub text: Option<String>,
+    pub path_text: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResultType {
+    pub id: BlockId,
+    pub is_navigable: Option<bool>,
+    pub score: Option<f64>,
+    pub highlight: Option<SearchHighlightType>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchData<'a> {
+    pub results: Vec<SearchResultType>,
+    pub total: i64,
+    #[serde(borrow)]
+    pub record_map: RecordMapType<'a>,
+}
+
+pub fn parse_search(input: String) -> Result<SearchData<'static>> {
+    let result: SearchData = serde_json::from_str(&input)?;
+    Ok(SearchData {
+        results: result.results,
+        total: result.total,
+        record_map: result.record_map.into_owned(),
+    })
+}
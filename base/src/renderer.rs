@@ -1,17 +1,98 @@
+use crate::diagnostics::{Diagnostic, DiagnosticReason};
+use crate::lookup::{BlockLookup, Child, Group};
+use crate::id::BlockId;
 use crate::parser::*;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 use std::marker::PhantomData;
 
+/// Where a block is in the render, and the block it was rendered from. Passed to every
+/// `BlockRenderer` hook, so templates can add anchors, data attributes or depth-dependent styling.
+pub struct BlockContext<'b> {
+    pub id: BlockId,
+    /// How many blocks are between this one and the block passed to `render`, which has a depth of 0.
+    pub depth: usize,
+    /// The block this one is rendered as a child of. `None` for the block passed to `render` and
+    /// the blocks passed to `render_children`.
+    pub parent: Option<&'b BaseValueType<'b>>,
+    /// The block's position among its siblings, not counting the ones that were skipped.
+    pub index: usize,
+    /// Holds the block's color, if it has one.
+    pub format: Option<&'b BlockFormatType>,
+    pub created_time: i64,
+    pub last_edited_time: i64,
+    /// The block itself, for anything else a template needs.
+    pub block: &'b BaseValueType<'b>,
+}
+
+/// Where a list item is in its list. Passed to the list hooks on `BlockRenderer` and `WrapperRenderer`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ListPosition {
+    /// The item's number, starting from 1. Numbered lists carry on counting after an interruption
+    /// (eg. a paragraph between two runs of items), like Notion does. Bulleted lists count from 1
+    /// in each run.
+    pub ordinal: usize,
+    /// How many lists of the same kind this item's list is nested in, so 0 for a top-level list.
+    /// Notion numbers nested lists as 1, a, i, 1, a, ... going down the levels.
+    pub level: usize,
+}
+
+impl Default for ListPosition {
+    fn default() -> Self {
+        ListPosition {
+            ordinal: 1,
+            level: 0,
+        }
+    }
+}
+
+impl<'b> BlockContext<'b> {
+    pub(crate) fn new(
+        block: &'b BaseValueType<'b>,
+        parent: Option<&'b BaseValueType<'b>>,
+        depth: usize,
+        index: usize,
+    ) -> Self {
+        BlockContext {
+            id: block.id,
+            depth,
+            parent,
+            index,
+            format: block.format.as_ref(),
+            created_time: block.created_time,
+            last_edited_time: block.last_edited_time,
+            block,
+        }
+    }
+}
+
 pub trait BlockRenderer<T> {
-    fn page_block(&self, children: T, text: Option<T>) -> T;
-    fn text_block(&self, children: T, text: Option<T>) -> T;
-    fn bulleted_list_block(&self, children: T, text: Option<T>) -> T;
-    fn numbered_list_block(&self, children: T, text: Option<T>) -> T;
-    fn toggle_block(&self, children: T, text: Option<T>) -> T;
-    fn quote_block(&self, children: T, text: Option<T>) -> T;
-    fn header_block(&self, children: T, text: Option<T>) -> T;
-    fn sub_header_block(&self, children: T, text: Option<T>) -> T;
-    fn sub_sub_header_block(&self, children: T, text: Option<T>) -> T;
-    fn divider_block(&self, children: T) -> T;
+    fn page_block(&self, context: &BlockContext, children: T, text: Option<T>) -> T;
+    fn text_block(&self, context: &BlockContext, children: T, text: Option<T>) -> T;
+    fn bulleted_list_block(
+        &self,
+        context: &BlockContext,
+        position: ListPosition,
+        children: T,
+        text: Option<T>,
+    ) -> T;
+    fn numbered_list_block(
+        &self,
+        context: &BlockContext,
+        position: ListPosition,
+        children: T,
+        text: Option<T>,
+    ) -> T;
+    fn toggle_block(&self, context: &BlockContext, children: T, text: Option<T>) -> T;
+    fn quote_block(&self, context: &BlockContext, children: T, text: Option<T>) -> T;
+    fn header_block(&self, context: &BlockContext, children: T, text: Option<T>) -> T;
+    fn sub_header_block(&self, context: &BlockContext, children: T, text: Option<T>) -> T;
+    fn sub_sub_header_block(&self, context: &BlockContext, children: T, text: Option<T>) -> T;
+    fn divider_block(&self, context: &BlockContext, children: T) -> T;
+    /// Called for blocks whose type the parser doesn't know about (`RootBlockType::Unknown`),
+    /// with the block's rendered children and its Notion type name. Implementations can
+    /// render a placeholder, pass the children through, or log the missing type.
+    fn unknown_block(&self, context: &BlockContext, children: T, type_name: &str) -> T;
     fn empty(&self) -> T;
 }
 
@@ -21,14 +102,41 @@ pub trait InlineRenderer<T> {
     fn italic(&self, acc: T) -> T;
     fn underline(&self, acc: T) -> T;
     fn strike(&self, acc: T) -> T;
-    fn link(&self, acc: T, link: &String) -> T;
+    fn link(&self, acc: T, link: &str) -> T;
     fn code(&self, acc: T) -> T;
     fn highlight(&self, acc: T, color: &ColorType) -> T;
 }
 
+/// What the renderer should do with a block, as decided by a `RenderPolicy`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PolicyDecision {
+    Render,
+    /// Leave the block and its children out of the output.
+    Redact,
+}
+
+/// A hook for deciding which blocks make it into the output, eg. to keep private sections off of
+/// a public site. Set one with `Renderer::policy`. Redacted blocks are reported as
+/// `DiagnosticReason::Redacted`. Policies have to be `Send` and `Sync`, since blocks can be
+/// rendered on several threads at once.
+pub trait RenderPolicy: Send + Sync {
+    /// Called for every block before it is rendered, with the current user's `role` on the block
+    /// and the permissions of the nearest page the block is in (the block itself, if it is a page).
+    /// `page_permissions` is empty if no page with permissions could be found in the block table.
+    fn check(
+        &self,
+        block: &BaseValueType,
+        role: &str,
+        page_permissions: &[PagePermissions],
+    ) -> PolicyDecision;
+}
+
 pub trait WrapperRenderer<T> {
-    fn bulleted_list_wrapper(&self, items: Vec<T>) -> T;
-    fn numbered_list_wrapper(&self, items: Vec<T>) -> T;
+    /// Wraps a run of list items. `first` is the position of the first item.
+    fn bulleted_list_wrapper(&self, items: Vec<T>, first: ListPosition) -> T;
+    /// Wraps a run of list items. `first` is the position of the first item, which holds the
+    /// number the list starts at.
+    fn numbered_list_wrapper(&self, items: Vec<T>, first: ListPosition) -> T;
     fn collect(&self, items: Vec<T>) -> T;
 }
 
@@ -49,10 +157,17 @@ pub trait WrapperRenderer<T> {
 /// of strict `serde_json` typings with graceful fallbacks. The renderer takes the same approach,
 /// gracefully falling back if it encounters JSON that it doesn't necessarily understand. If a block
 /// is invalid according to the latest version of its typings, it will skip rendering that block and
-/// all of its children.
+/// all of its children. Every block that is skipped or can't be fully rendered is recorded as a
+/// `Diagnostic`, which can be retrieved after rendering with `take_diagnostics`.
+///
+/// Deleted blocks (`alive: false`) can still be in the block table, so they and their children are
+/// skipped (and reported) unless the renderer is created with `include_dead_blocks(true)`. Blocks the
+/// current user has no access to (a role of `none`) are always skipped, and a `RenderPolicy` can
+/// redact any others.
 pub struct Renderer<'b, R, B, I, W> {
-    /// Property that caches the block table, making it easier to pass it down during recursion.
-    blocks: &'b BlockTableType,
+    /// Property that caches the block table (along with the settings for which blocks to skip), making
+    /// it easier to pass it down during recursion.
+    lookup: BlockLookup<'b>,
     /// Templating definition for rendering blocks with the trait type of `BlockRenderer`.
     block_renderer: B,
     /// Templating definition for rendering inline markup with the trait type of `InlineRenderer`.
@@ -61,8 +176,9 @@ pub struct Renderer<'b, R, B, I, W> {
     /// Blocks like bulleted lists or numbered lists need to be rendered inside of a `ul` tag (grouped together).
     /// The wrapper renderer takes care of this logic.
     wrapper_renderer: W,
-    /// A phantom value to get the compiler to shut up about an unused parameter.
-    p: PhantomData<R>,
+    /// A phantom value to get the compiler to shut up about an unused parameter. It's a function
+    /// pointer so the renderer can be shared between threads even if `R` can't be.
+    p: PhantomData<fn() -> R>,
 }
 
 impl<'b, R, B, I, W> Renderer<'b, R, B, I, W>
@@ -78,13 +194,13 @@ where
     /// blocks (and their children) on that block table.
     // TODO: Change blocks to be a string and handle all of the parsing.
     pub fn new(
-        blocks: &'b BlockTableType,
+        blocks: &'b BlockTableType<'b>,
         block_renderer: B,
         inline_renderer: I,
         wrapper_renderer: W,
     ) -> Self {
         Renderer {
-            blocks,
+            lookup: BlockLookup::new(blocks),
             block_renderer,
             inline_renderer,
             wrapper_renderer,
@@ -92,72 +208,49 @@ where
         }
     }
 
-    /// This is a helper function for determining if a specific `RootBlockType` needs
-    /// grouping or not. This should probably not be defined on the renderer's struct and should
-    /// probably be moved to the `parser` module.
-    // TODO: Move this to the parser module.
-    fn needs_grouping(&self, value: &RootBlockType) -> bool {
-        match value {
-            RootBlockType::BulletedList { properties: _ }
-            | RootBlockType::NumberedList { properties: _ } => true,
-            _ => false,
-        }
+    /// Sets whether deleted blocks (`alive: false`) are rendered. By default they are skipped along
+    /// with their children, and each one is reported as `DiagnosticReason::Deleted`.
+    pub fn include_dead_blocks(mut self, include: bool) -> Self {
+        self.lookup.include_dead = include;
+        self
     }
 
-    /// Determines if a given `RootBlockType` value can be grouped with the other
-    /// `BaseValueType`s found in the grouping accumulation vector. This only compares the type against
-    /// the first item in the vector; it is assumed that the only items that are allowed to be added to
-    /// the vector are items of the same block type. Therefore, we only need to check a single value,
-    /// instead of checking all values in the vector.
-    ///
-    /// # Arguments
-    /// - `value` - The value to compare against.
-    /// - `vector` - The vector to compare the value against.
-    // TODO: Rewrite the checking logic to use `needs_grouping`.
-    fn can_be_grouped<'a>(&self, value: &RootBlockType, vector: &Vec<&'a BaseValueType>) -> bool {
-        if vector.len() == 0 {
-            false
-        } else {
-            let first = &vector[0];
-            match (&first.block, value) {
-                (
-                    RootBlockType::BulletedList { properties: _ },
-                    RootBlockType::BulletedList { properties: _ },
-                )
-                | (
-                    RootBlockType::NumberedList { properties: _ },
-                    RootBlockType::NumberedList { properties: _ },
-                ) => true,
-                _ => false,
-            }
-        }
+    /// Sets the policy that decides which blocks are redacted, replacing any previous one.
+    pub fn policy<P: RenderPolicy + 'b>(mut self, policy: P) -> Self {
+        self.lookup.policy = Some(Box::new(policy));
+        self
     }
 
-    /// Renders a wrapper around the items in the accumulation `vector`. If the vector is empty,
-    /// returns an empty item, else, returns the wrapped elements.
-    ///
-    /// # Arguments
-    /// - `grouping_set` - The grouping set (a vector of `BaseValueType`s) to wrap.
-    fn render_wrapper<'a>(&self, grouping_set: &'a Vec<&'a BaseValueType>) -> R {
-        if grouping_set.len() == 0 {
-            return self.block_renderer.empty();
-        }
+    /// Returns the diagnostics collected since the last call, in the order they were found, and
+    /// clears them. Call this after `render` to find out which blocks were skipped and why.
+    pub fn take_diagnostics(&self) -> Vec<Diagnostic> {
+        self.lookup.take_diagnostics()
+    }
 
-        let first_block_value = grouping_set[0];
-        let rendered_items = grouping_set
-            .iter()
-            .map(|x| self.render(&x.id))
-            .collect::<Vec<_>>();
+    /// Puts the rendered children back together, wrapping each run of list items, and collects them.
+    /// `rendered` holds the result of each child from `plan_children`, in order.
+    fn assemble(&self, children: &[Child<'b>], groups: Vec<Group>, rendered: Vec<R>) -> R {
+        let mut rendered = rendered.into_iter();
+        let mut rendered_items = vec![];
 
-        match first_block_value.block {
-            RootBlockType::BulletedList { properties: _ } => {
-                self.wrapper_renderer.bulleted_list_wrapper(rendered_items)
-            }
-            RootBlockType::NumberedList { properties: _ } => {
-                self.wrapper_renderer.numbered_list_wrapper(rendered_items)
+        for group in groups {
+            match group {
+                Group::Block => rendered_items.extend(rendered.next()),
+                Group::List { start, len } => {
+                    let items = rendered.by_ref().take(len).collect();
+                    let first = children[start].position.unwrap_or_default();
+                    match children[start].value.block {
+                        RootBlockType::BulletedList { properties: _ } => rendered_items
+                            .push(self.wrapper_renderer.bulleted_list_wrapper(items, first)),
+                        RootBlockType::NumberedList { properties: _ } => rendered_items
+                            .push(self.wrapper_renderer.numbered_list_wrapper(items, first)),
+                        _ => {}
+                    }
+                }
             }
-            _ => self.block_renderer.empty(),
         }
+
+        self.wrapper_renderer.collect(rendered_items)
     }
 
     /// Renders an array of IDs (children). Calls `render` for each, wrapping them if necessary.
@@ -171,55 +264,25 @@ where
     /// # Arguments
     /// - `block_ids` - A string array of IDs to render.
     // TODO: If the resulting array is empty, don't call `collect` and instead call `empty` on the `BlockRenderer`.
-    pub fn render_children<'a>(&self, block_ids: &Vec<String>) -> R {
-        // Defines an accumaltor variable that is a tuple. The first item in the tuple
-        // is an array of completely rendered items, and the second item is a temporary
-        // accumulator array for grouping.
-        // This would be a good candidate to replace with an enum that has more self-explanatory names.
-        let accumulator: (Vec<R>, Vec<&'a BaseValueType>) = (vec![], vec![]);
-
-        // Shadowing the variable here because it's still an accumulator.
-        let accumulator = block_ids.iter().fold(
-            accumulator,
-            |(mut rendered_items, mut grouping_set), block_id| {
-                let element = self.blocks.get(block_id);
-
-                if let Some(block) = element {
-                    if let Either::Left(block_value) = &block.value {
-                        let rendered = self.render(&block_id);
-
-                        let block_can_be_grouped = self.needs_grouping(&block_value.block)
-                            && (self.can_be_grouped(&block_value.block, &grouping_set)
-                                || grouping_set.len() == 0);
-
-                        if block_can_be_grouped {
-                            grouping_set.push(block_value);
-                            return (rendered_items, grouping_set);
-                        } else if self.needs_grouping(&block_value.block) {
-                            grouping_set.push(&block_value);
-                            rendered_items.push(self.render_wrapper(&grouping_set));
-                            return (rendered_items, vec![]);
-                        } else if grouping_set.len() > 0 {
-                            rendered_items.push(self.render_wrapper(&grouping_set));
-                            rendered_items.push(rendered);
-                            return (rendered_items, vec![]);
-                        } else {
-                            rendered_items.push(rendered);
-                            return (rendered_items, grouping_set);
-                        }
-                    }
-                }
-
-                return (rendered_items, grouping_set);
-            },
-        );
+    pub fn render_children(&self, block_ids: &[BlockId]) -> R {
+        self.render_children_of(block_ids, None, 0, ListPosition::default())
+    }
 
-        let (mut rendered_items, grouping_set) = accumulator;
-        if grouping_set.len() > 0 {
-            rendered_items.push(self.render_wrapper(&grouping_set));
-        }
+    /// Renders the children of `parent` (or top-level blocks, if there is no parent) at `depth`.
+    /// `parent_position` is the parent's list position, which is only used if it is a list item.
+    fn render_children_of(
+        &self,
+        block_ids: &[BlockId],
+        parent: Option<&'b BaseValueType<'b>>,
+        depth: usize,
+        parent_position: ListPosition,
+    ) -> R {
+        let mut rendered = vec![];
+        let (children, groups) = self.lookup.plan_children(block_ids, parent, parent_position, |child| {
+            rendered.push(self.render_value(child.value, parent, depth, child.index, child.position));
+        });
 
-        self.wrapper_renderer.collect(rendered_items)
+        self.assemble(&children, groups, rendered)
     }
 
     /// Renders a piece of text (`FormattedText`), wrapping it with the appropriate inline styles.
@@ -236,7 +299,7 @@ where
     /// # Arguments
     /// - text - A vector of `FormattedText` to render. Can have zero items if necessary.
     // TODO: Change `text.iter().fold` to use `text.iter().map` and `collect` at the end only once.
-    pub fn render_text(&self, text: &Vec<FormattedText>) -> R {
+    pub fn render_text(&self, text: &[FormattedText]) -> R {
         let empty_initial_value = self.block_renderer.empty();
 
         text.iter().fold(empty_initial_value, |acc, current_text| {
@@ -244,7 +307,7 @@ where
                 let initial_text = self.inline_renderer.text(&current_text.text);
 
                 let wrapped_formatted_text = formatting.iter().fold(initial_text, |formatting_acc, current_format| {
-                    return match current_format {
+                    match current_format {
                         FormatType::NoContext(format_type) => match format_type {
                             NoContextFormat::Bold => self.inline_renderer.bold(formatting_acc),
                             NoContextFormat::Italic => self.inline_renderer.italic(formatting_acc),
@@ -258,14 +321,14 @@ where
                             ContextFormat::Highlight(highlight_color) => self.inline_renderer.highlight(formatting_acc, highlight_color),
                             _ => formatting_acc,
                         },
-                    };
+                    }
                 });
                 return self.wrapper_renderer.collect(vec![acc, wrapped_formatted_text]);
             }
 
-            return self
+            self
                 .wrapper_renderer
-                .collect(vec![acc, self.inline_renderer.text(&current_text.text)]);
+                .collect(vec![acc, self.inline_renderer.text(&current_text.text)])
         })
     }
 
@@ -278,69 +341,171 @@ where
     ///
     /// # Arguments
     /// - `block_id` - The ID of the block to render.
-    pub fn render(&self, block_id: &str) -> R {
+    pub fn render(&self, block_id: &BlockId) -> R {
         // We want to always return *something*, so this function doesn't deal with error cases
-        if let Some(root_block) = self.blocks.get(block_id) {
-            if let Either::Left(block_value) = &root_block.value {
-                let default_child_ids: &Vec<String> = &vec![];
-                let child_ids = block_value.content.as_ref().unwrap_or(default_child_ids);
-
-                let children = self.render_children(&child_ids);
-
-                return match &block_value.block {
-                    RootBlockType::Page {
-                        format: _,
-                        file_ids: _,
-                        properties,
-                    } => self
-                        .block_renderer
-                        .page_block(children, Some(self.render_text(&properties.title))),
-                    RootBlockType::Text { properties } => self.block_renderer.text_block(
-                        children,
-                        properties.as_ref().map(|x| self.render_text(&x.title)),
-                    ),
-                    RootBlockType::BulletedList { properties } => {
-                        self.block_renderer.bulleted_list_block(
-                            children,
-                            properties.as_ref().map(|x| self.render_text(&x.title)),
-                        )
-                    }
-                    RootBlockType::NumberedList { properties } => {
-                        self.block_renderer.numbered_list_block(
-                            children,
-                            properties.as_ref().map(|x| self.render_text(&x.title)),
-                        )
-                    }
-                    RootBlockType::Quote { properties } => self.block_renderer.quote_block(
-                        children,
-                        properties.as_ref().map(|x| self.render_text(&x.title)),
-                    ),
-                    RootBlockType::Header { properties } => self.block_renderer.header_block(
-                        children,
-                        properties.as_ref().map(|x| self.render_text(&x.title)),
-                    ),
-                    RootBlockType::SubHeader { properties } => {
-                        self.block_renderer.sub_header_block(
-                            children,
-                            properties.as_ref().map(|x| self.render_text(&x.title)),
-                        )
-                    }
-                    RootBlockType::SubSubHeader { properties } => {
-                        self.block_renderer.sub_sub_header_block(
-                            children,
-                            properties.as_ref().map(|x| self.render_text(&x.title)),
-                        )
-                    }
-                    RootBlockType::Toggle { properties } => self.block_renderer.toggle_block(
-                        children,
-                        properties.as_ref().map(|x| self.render_text(&x.title)),
-                    ),
-                    RootBlockType::Divider => self.block_renderer.divider_block(children),
-                    _ => self.block_renderer.empty(),
-                };
+        match self.lookup.get_block(block_id) {
+            Some(block_value) => self.render_value(block_value, None, 0, 0, None),
+            None => self.block_renderer.empty(),
+        }
+    }
+
+    /// Renders a block that has already been looked up, along with its children.
+    fn render_value(
+        &self,
+        block_value: &'b BaseValueType<'b>,
+        parent: Option<&'b BaseValueType<'b>>,
+        depth: usize,
+        index: usize,
+        list_position: Option<ListPosition>,
+    ) -> R {
+        let position = list_position.unwrap_or_default();
+        let child_ids = block_value.content.as_deref().unwrap_or_default();
+        let children = self.render_children_of(child_ids, Some(block_value), depth + 1, position);
+
+        self.render_block(block_value, parent, depth, index, position, children)
+    }
+
+    /// Calls the `BlockRenderer` hook for a block's type, given its rendered children.
+    fn render_block(
+        &self,
+        block_value: &'b BaseValueType<'b>,
+        parent: Option<&'b BaseValueType<'b>>,
+        depth: usize,
+        index: usize,
+        position: ListPosition,
+        children: R,
+    ) -> R {
+        let block_id = &block_value.id;
+        let context = &BlockContext::new(block_value, parent, depth, index);
+
+        match &block_value.block {
+            RootBlockType::Page {
+                format: _,
+                file_ids: _,
+                properties,
+                permissions: _,
+            } => self
+                .block_renderer
+                .page_block(context, children, Some(self.render_text(&properties.title))),
+            RootBlockType::Text { properties } => self.block_renderer.text_block(
+                context,
+                children,
+                properties.as_ref().map(|x| self.render_text(&x.title)),
+            ),
+            RootBlockType::BulletedList { properties } => {
+                self.block_renderer.bulleted_list_block(
+                    context,
+                    position,
+                    children,
+                    properties.as_ref().map(|x| self.render_text(&x.title)),
+                )
+            }
+            RootBlockType::NumberedList { properties } => {
+                self.block_renderer.numbered_list_block(
+                    context,
+                    position,
+                    children,
+                    properties.as_ref().map(|x| self.render_text(&x.title)),
+                )
+            }
+            RootBlockType::Quote { properties } => self.block_renderer.quote_block(
+                context,
+                children,
+                properties.as_ref().map(|x| self.render_text(&x.title)),
+            ),
+            RootBlockType::Header { properties } => self.block_renderer.header_block(
+                context,
+                children,
+                properties.as_ref().map(|x| self.render_text(&x.title)),
+            ),
+            RootBlockType::SubHeader { properties } => {
+                self.block_renderer.sub_header_block(
+                    context,
+                    children,
+                    properties.as_ref().map(|x| self.render_text(&x.title)),
+                )
             }
+            RootBlockType::SubSubHeader { properties } => {
+                self.block_renderer.sub_sub_header_block(
+                    context,
+                    children,
+                    properties.as_ref().map(|x| self.render_text(&x.title)),
+                )
+            }
+            RootBlockType::Toggle { properties } => self.block_renderer.toggle_block(
+                context,
+                children,
+                properties.as_ref().map(|x| self.render_text(&x.title)),
+            ),
+            RootBlockType::Divider => self.block_renderer.divider_block(context, children),
+            RootBlockType::Unknown { type_name, raw: _ } => {
+                self.lookup.report(block_id, Some(type_name), DiagnosticReason::UnsupportedType);
+                self.block_renderer.unknown_block(context, children, type_name)
+            }
+            unsupported => {
+                let block_type = Some(unsupported.type_name());
+                self.lookup.report(block_id, block_type, DiagnosticReason::UnsupportedType);
+                self.block_renderer.empty()
+            }
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<'b, R, B, I, W> Renderer<'b, R, B, I, W>
+where
+    R: Send,
+    B: BlockRenderer<R> + Sync,
+    I: InlineRenderer<R> + Sync,
+    W: WrapperRenderer<R> + Sync,
+{
+    /// Like `render`, but renders sibling blocks (and everything under them) in parallel on rayon's
+    /// thread pool. Returns the same result as `render`, but diagnostics from different subtrees
+    /// can be collected in any order.
+    pub fn render_parallel(&self, block_id: &BlockId) -> R {
+        match self.lookup.get_block(block_id) {
+            Some(block_value) => self.render_value_parallel(block_value, None, 0, 0, None),
+            None => self.block_renderer.empty(),
         }
+    }
+
+    /// Like `render_children`, but renders the blocks in parallel. See `render_parallel`.
+    pub fn render_children_parallel(&self, block_ids: &[BlockId]) -> R {
+        self.render_children_of_parallel(block_ids, None, 0, ListPosition::default())
+    }
+
+    fn render_value_parallel(
+        &self,
+        block_value: &'b BaseValueType<'b>,
+        parent: Option<&'b BaseValueType<'b>>,
+        depth: usize,
+        index: usize,
+        list_position: Option<ListPosition>,
+    ) -> R {
+        let position = list_position.unwrap_or_default();
+        let child_ids = block_value.content.as_deref().unwrap_or_default();
+        let children =
+            self.render_children_of_parallel(child_ids, Some(block_value), depth + 1, position);
+
+        self.render_block(block_value, parent, depth, index, position, children)
+    }
+
+    fn render_children_of_parallel(
+        &self,
+        block_ids: &[BlockId],
+        parent: Option<&'b BaseValueType<'b>>,
+        depth: usize,
+        parent_position: ListPosition,
+    ) -> R {
+        // The children are all looked up first, so the grouping is the same as when rendering in order.
+        let (children, groups) = self.lookup.plan_children(block_ids, parent, parent_position, |_| {});
+        let rendered = children
+            .par_iter()
+            .map(|child| {
+                self.render_value_parallel(child.value, parent, depth, child.index, child.position)
+            })
+            .collect();
 
-        self.block_renderer.empty()
+        self.assemble(&children, groups, rendered)
     }
 }
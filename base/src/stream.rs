@@ -0,0 +1,254 @@
+use crate::diagnostics::{Diagnostic, DiagnosticReason};
+use crate::id::BlockId;
+use crate::lookup::{BlockLookup, Child, Group};
+use crate::parser::*;
+use crate::renderer::{BlockContext, ListPosition, RenderPolicy};
+use std::fmt::{self, Write};
+use std::io;
+
+/// The kind of list a run of list items is in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ListKind {
+    Bulleted,
+    Numbered,
+}
+
+/// Templating for `StreamingRenderer`. Instead of building up and returning a value for each
+/// block like `BlockRenderer`, each hook writes its part of the output straight into `out`.
+///
+/// A block is written as `open_block`, the block's text, `open_children`, the block's children,
+/// then `close_block`. Blocks that don't have text (like dividers) still get all three calls. The
+/// block's type is in `context.block.block`.
+pub trait StreamRenderer {
+    /// Writes the start of a block, up to where its text goes.
+    fn open_block(
+        &self,
+        out: &mut dyn Write,
+        context: &BlockContext,
+        position: ListPosition,
+    ) -> fmt::Result;
+    /// Writes whatever goes between a block's text and its children.
+    fn open_children(&self, out: &mut dyn Write, context: &BlockContext) -> fmt::Result;
+    /// Writes the end of a block, after its children.
+    fn close_block(&self, out: &mut dyn Write, context: &BlockContext) -> fmt::Result;
+    /// Writes the start of a run of list items. `first` is the position of the first item.
+    fn open_list(&self, out: &mut dyn Write, kind: ListKind, first: ListPosition) -> fmt::Result;
+    fn close_list(&self, out: &mut dyn Write, kind: ListKind) -> fmt::Result;
+    /// Writes a piece of plain text, escaping it if needed.
+    fn text(&self, out: &mut dyn Write, text: &str) -> fmt::Result;
+    /// Writes the start of an inline style. Formats are opened outermost first, and closed in reverse.
+    fn open_format(&self, out: &mut dyn Write, format: &FormatType) -> fmt::Result;
+    fn close_format(&self, out: &mut dyn Write, format: &FormatType) -> fmt::Result;
+}
+
+/// Throws away everything written to it. The children of blocks that can't be rendered are written
+/// here, so they still get looked up and reported like they are by `Renderer`.
+struct Discard;
+
+impl Write for Discard {
+    fn write_str(&mut self, _: &str) -> fmt::Result {
+        Ok(())
+    }
+}
+
+/// Lets the renderer write into an `io::Write`. `fmt::Error` can't hold the reason a write
+/// failed, so the first IO error is kept here.
+struct IoWriter<W> {
+    inner: W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|error| {
+            self.error.get_or_insert(error);
+            fmt::Error
+        })
+    }
+}
+
+/// Renders blocks like `Renderer`, but writes the output straight into a `fmt::Write` (or an
+/// `io::Write`, like an HTTP body) as it goes, instead of building up a value for every block.
+/// Blocks are skipped and reported the same way as they are by `Renderer`, and list items are
+/// grouped and numbered the same way.
+///
+/// Each block's children are all looked up before any of them are written, so diagnostics can come
+/// in a slightly different order than they do with `Renderer`.
+pub struct StreamingRenderer<'b, S> {
+    lookup: BlockLookup<'b>,
+    stream_renderer: S,
+}
+
+impl<'b, S: StreamRenderer> StreamingRenderer<'b, S> {
+    pub fn new(blocks: &'b BlockTableType<'b>, stream_renderer: S) -> Self {
+        StreamingRenderer {
+            lookup: BlockLookup::new(blocks),
+            stream_renderer,
+        }
+    }
+
+    /// Sets whether deleted blocks (`alive: false`) are rendered. See `Renderer::include_dead_blocks`.
+    pub fn include_dead_blocks(mut self, include: bool) -> Self {
+        self.lookup.include_dead = include;
+        self
+    }
+
+    /// Sets the policy that decides which blocks are redacted. See `Renderer::policy`.
+    pub fn policy<P: RenderPolicy + 'b>(mut self, policy: P) -> Self {
+        self.lookup.policy = Some(Box::new(policy));
+        self
+    }
+
+    /// Returns the diagnostics collected since the last call and clears them.
+    pub fn take_diagnostics(&self) -> Vec<Diagnostic> {
+        self.lookup.take_diagnostics()
+    }
+
+    /// Renders a block and its children into `out`. Nothing is written if the block can't be
+    /// rendered. Only fails if writing to `out` does.
+    pub fn render(&self, block_id: &BlockId, out: &mut dyn Write) -> fmt::Result {
+        match self.lookup.get_block(block_id) {
+            Some(value) => {
+                let child = Child {
+                    value,
+                    index: 0,
+                    position: None,
+                };
+                self.render_child(&child, None, 0, out)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Renders an array of IDs into `out`, wrapping list items like `Renderer::render_children`.
+    pub fn render_children(&self, block_ids: &[BlockId], out: &mut dyn Write) -> fmt::Result {
+        self.render_children_of(block_ids, None, 0, ListPosition::default(), out)
+    }
+
+    /// Renders a block and its children into an `io::Write`. The writer isn't buffered, so wrap it in
+    /// an `io::BufWriter` if small writes are expensive.
+    pub fn render_io<W: io::Write>(&self, block_id: &BlockId, out: W) -> io::Result<()> {
+        let mut writer = IoWriter {
+            inner: out,
+            error: None,
+        };
+
+        self.render(block_id, &mut writer).map_err(|_| {
+            writer
+                .error
+                .unwrap_or_else(|| io::Error::other("formatter error"))
+        })
+    }
+
+    fn render_children_of(
+        &self,
+        block_ids: &[BlockId],
+        parent: Option<&'b BaseValueType<'b>>,
+        depth: usize,
+        parent_position: ListPosition,
+        out: &mut dyn Write,
+    ) -> fmt::Result {
+        let (children, groups) =
+            self.lookup
+                .plan_children(block_ids, parent, parent_position, |_| {});
+
+        // The index of the first child in the next group.
+        let mut next = 0;
+        for group in groups {
+            match group {
+                Group::Block => {
+                    self.render_child(&children[next], parent, depth, out)?;
+                    next += 1;
+                }
+                Group::List { start, len } => {
+                    let kind = match children[start].value.block {
+                        RootBlockType::NumberedList { properties: _ } => ListKind::Numbered,
+                        _ => ListKind::Bulleted,
+                    };
+                    let first = children[start].position.unwrap_or_default();
+
+                    self.stream_renderer.open_list(out, kind, first)?;
+                    for child in &children[start..start + len] {
+                        self.render_child(child, parent, depth, out)?;
+                    }
+                    self.stream_renderer.close_list(out, kind)?;
+                    next = start + len;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes a block that has already been looked up, along with its children.
+    fn render_child(
+        &self,
+        child: &Child<'b>,
+        parent: Option<&'b BaseValueType<'b>>,
+        depth: usize,
+        out: &mut dyn Write,
+    ) -> fmt::Result {
+        let block_value = child.value;
+        let block_id = &block_value.id;
+        let position = child.position.unwrap_or_default();
+        let child_ids = block_value.content.as_deref().unwrap_or_default();
+
+        let text = match &block_value.block {
+            RootBlockType::Page { properties, .. } => Some(&properties.title[..]),
+            RootBlockType::Text { properties }
+            | RootBlockType::BulletedList { properties }
+            | RootBlockType::NumberedList { properties }
+            | RootBlockType::Toggle { properties }
+            | RootBlockType::Quote { properties }
+            | RootBlockType::Header { properties }
+            | RootBlockType::SubHeader { properties }
+            | RootBlockType::SubSubHeader { properties } => {
+                Some(properties.as_ref().map_or(&[][..], |x| &x.title[..]))
+            }
+            RootBlockType::Divider => None,
+            RootBlockType::Unknown { type_name, raw: _ } => {
+                self.lookup
+                    .report(block_id, Some(type_name), DiagnosticReason::UnsupportedType);
+                None
+            }
+            unsupported => {
+                let block_type = Some(unsupported.type_name());
+                self.lookup
+                    .report(block_id, block_type, DiagnosticReason::UnsupportedType);
+                return self.render_children_of(
+                    child_ids,
+                    Some(block_value),
+                    depth + 1,
+                    position,
+                    &mut Discard,
+                );
+            }
+        };
+
+        let context = &BlockContext::new(block_value, parent, depth, child.index);
+        self.stream_renderer.open_block(out, context, position)?;
+        if let Some(text) = text {
+            self.render_text(text, out)?;
+        }
+        self.stream_renderer.open_children(out, context)?;
+        self.render_children_of(child_ids, Some(block_value), depth + 1, position, out)?;
+        self.stream_renderer.close_block(out, context)
+    }
+
+    /// Writes a piece of text (`FormattedText`), wrapped in its inline styles. The first style is the
+    /// innermost one, like it is for `Renderer::render_text`.
+    pub fn render_text(&self, text: &[FormattedText], out: &mut dyn Write) -> fmt::Result {
+        for current_text in text {
+            let formatting = current_text.formatting.as_deref().unwrap_or_default();
+            for format in formatting.iter().rev() {
+                self.stream_renderer.open_format(out, format)?;
+            }
+            self.stream_renderer.text(out, &current_text.text)?;
+            for format in formatting {
+                self.stream_renderer.close_format(out, format)?;
+            }
+        }
+
+        Ok(())
+    }
+}
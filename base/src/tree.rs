@@ -0,0 +1,238 @@
+use crate::id::BlockId;
+use crate::parser::{BaseValueType, BlockTableType, BlockType, Either, RecordMapType};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::ops::Index;
+
+/// A node's position in a `Document`. Only valid for the document it came from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+/// A block in a `Document`, along with links to the blocks around it.
+#[derive(Debug)]
+pub struct Node<'a> {
+    pub value: BaseValueType<'a>,
+    /// How many blocks are between this block and the root. The root's depth is 0.
+    pub depth: usize,
+    parent: Option<NodeId>,
+    /// The block's position in its parent's children.
+    index: usize,
+    children: Vec<NodeId>,
+}
+
+impl<'a> Node<'a> {
+    pub fn id(&self) -> BlockId {
+        self.value.id
+    }
+}
+
+/// A page as a tree of blocks, built from a record map by following each block's `content`,
+/// starting from a root block.
+///
+/// The document owns its blocks, which are stored in a single arena and referred to by `NodeId`,
+/// so any block's parent, children and siblings can be found without looking up IDs in the block
+/// table. Blocks that aren't reachable from the root, are missing from the block table, or
+/// failed to parse are left out, so a node's children may be fewer than its `content`.
+#[derive(Debug)]
+pub struct Document<'a> {
+    nodes: Vec<Node<'a>>,
+    ids: HashMap<BlockId, NodeId>,
+}
+
+/// Takes a parsed block out of the block table.
+fn take<'a>(blocks: &mut BlockTableType<'a>, id: &BlockId) -> Option<BaseValueType<'a>> {
+    match blocks.remove(id) {
+        Some(BlockType {
+            value: Either::Left(value),
+            ..
+        }) => Some(value),
+        _ => None,
+    }
+}
+
+impl<'a> Document<'a> {
+    /// Builds a document out of the blocks under `root`, including deleted (`alive: false`) ones.
+    pub fn new(record_map: RecordMapType<'a>, root: &BlockId) -> Result<Self> {
+        Document::build(record_map.block, root, true)
+    }
+
+    /// Builds a document out of the blocks under `root`, leaving out deleted (`alive: false`)
+    /// blocks and everything under them.
+    pub fn without_dead(record_map: RecordMapType<'a>, root: &BlockId) -> Result<Self> {
+        Document::build(record_map.block, root, false)
+    }
+
+    fn build(mut blocks: BlockTableType<'a>, root: &BlockId, keep_dead: bool) -> Result<Self> {
+        let value = match take(&mut blocks, root) {
+            Some(value) if keep_dead || value.alive => value,
+            Some(_) => return Err(anyhow!("the root block {} has been deleted", root)),
+            None => {
+                return Err(anyhow!(
+                    "the root block {} is missing or failed to parse",
+                    root
+                ))
+            }
+        };
+
+        let mut document = Document {
+            nodes: vec![],
+            ids: HashMap::new(),
+        };
+        document.push(value, None);
+
+        // Blocks are taken out of the table as they're added, so a block listed in more than
+        // one place (or in a cycle) is only added once, where it comes first in pre-order. Each
+        // entry on the stack is a node and how many of its `content` have been looked at.
+        let mut stack = vec![(NodeId(0), 0)];
+        while let Some((parent, next)) = stack.last_mut() {
+            let parent = *parent;
+            let content = document[parent].value.content.as_deref().unwrap_or_default();
+            let block_id = match content.get(*next) {
+                Some(block_id) => *block_id,
+                None => {
+                    stack.pop();
+                    continue;
+                }
+            };
+            *next += 1;
+
+            match take(&mut blocks, &block_id) {
+                Some(value) if keep_dead || value.alive => {
+                    stack.push((document.push(value, Some(parent)), 0));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(document)
+    }
+
+    fn push(&mut self, value: BaseValueType<'a>, parent: Option<NodeId>) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        let (depth, index) = match parent {
+            Some(parent) => {
+                let parent = &mut self.nodes[parent.0];
+                parent.children.push(id);
+                (parent.depth + 1, parent.children.len() - 1)
+            }
+            None => (0, 0),
+        };
+
+        self.ids.insert(value.id, id);
+        self.nodes.push(Node {
+            value,
+            depth,
+            parent,
+            index,
+            children: vec![],
+        });
+        id
+    }
+
+    pub fn root(&self) -> NodeId {
+        NodeId(0)
+    }
+
+    /// The number of blocks in the document.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Always false, since a document always has its root block.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn get(&self, node: NodeId) -> Option<&Node<'a>> {
+        self.nodes.get(node.0)
+    }
+
+    /// Finds the node of a block by its ID.
+    pub fn find(&self, block_id: &BlockId) -> Option<NodeId> {
+        self.ids.get(block_id).copied()
+    }
+
+    pub fn parent(&self, node: NodeId) -> Option<NodeId> {
+        self[node].parent
+    }
+
+    /// The node's children, in order.
+    pub fn children(
+        &self,
+        node: NodeId,
+    ) -> impl DoubleEndedIterator<Item = NodeId> + ExactSizeIterator + '_ {
+        self[node].children.iter().copied()
+    }
+
+    pub fn next_sibling(&self, node: NodeId) -> Option<NodeId> {
+        let parent = self.parent(node)?;
+        self[parent].children.get(self[node].index + 1).copied()
+    }
+
+    pub fn previous_sibling(&self, node: NodeId) -> Option<NodeId> {
+        let parent = self.parent(node)?;
+        let index = self[node].index.checked_sub(1)?;
+        self[parent].children.get(index).copied()
+    }
+
+    /// The node's parent, its parent's parent, and so on, up to and including the root.
+    pub fn ancestors(&self, node: NodeId) -> Ancestors<'_, 'a> {
+        Ancestors {
+            document: self,
+            next: self.parent(node),
+        }
+    }
+
+    /// The node and everything under it, in pre-order (each block comes before its children).
+    pub fn pre_order(&self, node: NodeId) -> PreOrder<'_, 'a> {
+        PreOrder {
+            document: self,
+            stack: vec![node],
+        }
+    }
+
+    /// Every node in the document, in pre-order.
+    pub fn iter(&self) -> PreOrder<'_, 'a> {
+        self.pre_order(self.root())
+    }
+}
+
+impl<'a> Index<NodeId> for Document<'a> {
+    type Output = Node<'a>;
+
+    fn index(&self, node: NodeId) -> &Node<'a> {
+        &self.nodes[node.0]
+    }
+}
+
+/// Iterates over a node's ancestors, from its parent up to the root. Created by `Document::ancestors`.
+pub struct Ancestors<'d, 'a> {
+    document: &'d Document<'a>,
+    next: Option<NodeId>,
+}
+
+impl<'d, 'a> Iterator for Ancestors<'d, 'a> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let node = self.next?;
+        self.next = self.document.parent(node);
+        Some(node)
+    }
+}
+
+/// Iterates over a node and its descendants in pre-order. Created by `Document::pre_order`.
+pub struct PreOrder<'d, 'a> {
+    document: &'d Document<'a>,
+    stack: Vec<NodeId>,
+}
+
+impl<'d, 'a> Iterator for PreOrder<'d, 'a> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let node = self.stack.pop()?;
+        self.stack.extend(self.document.children(node).rev());
+        Some(node)
+    }
+}
@@ -0,0 +1,223 @@
+mod common;
+
+use anyhow::{anyhow, Result};
+use base::async_renderer::{AsyncRenderer, BlockSource};
+use base::diagnostics::{Diagnostic, DiagnosticReason};
+use base::id::BlockId;
+use base::parser::{parse_owned, BlockTableType};
+use common::{block, chunk, id, Outline};
+use futures_util::future::{self, BoxFuture, FutureExt};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::task::Poll;
+
+/// A `BlockSource` that serves blocks from memory, and keeps track of its fetches.
+#[derive(Default)]
+struct Source {
+    blocks: HashMap<BlockId, Value>,
+    /// IDs that fail to fetch, rather than coming back without the block.
+    broken: Vec<BlockId>,
+    fetched: Mutex<Vec<BlockId>>,
+    in_flight: AtomicUsize,
+    most_in_flight: AtomicUsize,
+}
+
+impl Source {
+    fn new(blocks: Vec<Value>) -> Self {
+        let blocks = blocks
+            .into_iter()
+            .map(|value| {
+                (
+                    BlockId::parse(value["id"].as_str().unwrap()).unwrap(),
+                    value,
+                )
+            })
+            .collect();
+        Source {
+            blocks,
+            ..Source::default()
+        }
+    }
+
+    fn fetched(&self) -> Vec<BlockId> {
+        self.fetched.lock().unwrap().clone()
+    }
+}
+
+/// Returns `Pending` once, so other fetches get to start before this one finishes.
+async fn yield_once() {
+    let mut yielded = false;
+    future::poll_fn(|cx| {
+        if yielded {
+            Poll::Ready(())
+        } else {
+            yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    })
+    .await
+}
+
+impl Source {
+    async fn fetch_block(&self, block_id: &BlockId) -> Result<BlockTableType<'static>> {
+        self.fetched.lock().unwrap().push(*block_id);
+        let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.most_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+        yield_once().await;
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+        if self.broken.contains(block_id) {
+            return Err(anyhow!("couldn't reach the source"));
+        }
+        // Like Notion, blocks that don't exist are left out of the response.
+        let table = match self.blocks.get(block_id) {
+            Some(value) => json!({ block_id.to_string(): { "role": "reader", "value": value } }),
+            None => json!({}),
+        };
+        Ok(BlockTableType::deserialize(table)?)
+    }
+}
+
+/// Implemented for a reference, so the tests can look at the source after rendering.
+impl BlockSource for &Source {
+    fn fetch<'s>(
+        &'s self,
+        block_id: &'s BlockId,
+    ) -> BoxFuture<'s, Result<BlockTableType<'static>>> {
+        self.fetch_block(block_id).boxed()
+    }
+}
+
+/// A block table holding only the page, block 0, with the children `content`.
+fn page(content: &[u32]) -> BlockTableType<'static> {
+    parse_owned(chunk(vec![block(0, "page", content)]))
+        .unwrap()
+        .record_map
+        .block
+}
+
+fn ids(numbers: &[u32]) -> Vec<BlockId> {
+    numbers.iter().map(|n| id(*n)).collect()
+}
+
+#[tokio::test]
+async fn fetches_missing_blocks_and_renders_them_in_order() {
+    let source = Source::new(vec![
+        block(1, "text", &[4, 5]),
+        block(2, "text", &[]),
+        block(3, "text", &[]),
+        block(4, "text", &[]),
+        block(5, "text", &[]),
+    ]);
+    let mut blocks = page(&[1, 2, 3]);
+    let renderer = AsyncRenderer::new(&source);
+
+    let outline = Outline::default();
+    let (result, diagnostics) = renderer
+        .render(
+            &mut blocks,
+            &id(0),
+            outline.clone(),
+            outline.clone(),
+            outline,
+        )
+        .await;
+
+    assert_eq!(result, "0[1[4 5] 2 3]");
+    assert_eq!(diagnostics, vec![]);
+    // A level at a time, in order.
+    assert_eq!(source.fetched(), ids(&[1, 2, 3, 4, 5]));
+    assert_eq!(blocks.len(), 6);
+}
+
+#[tokio::test]
+async fn reports_unknown_blocks_without_fetching_them_again() {
+    let mut source = Source::new(vec![block(1, "text", &[2, 3])]);
+    source.broken.push(id(3));
+    // 2 is listed under both the page and 1.
+    let mut blocks = page(&[1, 2]);
+    let renderer = AsyncRenderer::new(&source);
+
+    let outline = Outline::default();
+    let (result, diagnostics) = renderer
+        .render(
+            &mut blocks,
+            &id(0),
+            outline.clone(),
+            outline.clone(),
+            outline,
+        )
+        .await;
+
+    assert_eq!(result, "0[1]");
+    assert_eq!(source.fetched(), ids(&[1, 2, 3]));
+    let missing = |n| Diagnostic {
+        block_id: id(n),
+        block_type: None,
+        reason: DiagnosticReason::MissingFromTable,
+    };
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic {
+                block_id: id(3),
+                block_type: None,
+                reason: DiagnosticReason::FetchFailure(String::from("couldn't reach the source")),
+            },
+            missing(2),
+            missing(3),
+            missing(2),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn stops_fetching_at_the_limit() {
+    let source = Source::new(vec![
+        block(1, "text", &[]),
+        block(2, "text", &[]),
+        block(3, "text", &[]),
+    ]);
+    let mut blocks = page(&[1, 2, 3]);
+    let renderer = AsyncRenderer::new(&source).max_fetches(2);
+
+    let outline = Outline::default();
+    let (result, diagnostics) = renderer
+        .render(
+            &mut blocks,
+            &id(0),
+            outline.clone(),
+            outline.clone(),
+            outline,
+        )
+        .await;
+
+    assert_eq!(result, "0[1 2]");
+    assert_eq!(source.fetched(), ids(&[1, 2]));
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic {
+            block_id: id(3),
+            block_type: None,
+            reason: DiagnosticReason::MissingFromTable,
+        }]
+    );
+}
+
+#[tokio::test]
+async fn keeps_to_the_concurrency_limit() {
+    let children: Vec<u32> = (1..=10).collect();
+    let source = Source::new(children.iter().map(|n| block(*n, "text", &[])).collect());
+    let mut blocks = page(&children);
+    let renderer = AsyncRenderer::new(&source).concurrency(3);
+
+    let diagnostics = renderer.fetch_missing(&mut blocks, &id(0)).await;
+
+    assert_eq!(diagnostics, vec![]);
+    assert_eq!(source.fetched(), ids(&children));
+    assert_eq!(source.most_in_flight.load(Ordering::SeqCst), 3);
+}
@@ -0,0 +1,209 @@
+//! Builds `loadPageChunk` responses for the tests, and renders them as an outline of block numbers.
+#![allow(dead_code)]
+
+use base::id::BlockId;
+use base::parser::ColorType;
+use base::renderer::{BlockContext, BlockRenderer, InlineRenderer, ListPosition, WrapperRenderer};
+use serde_json::{json, Map, Value};
+use std::sync::{Arc, Mutex};
+
+pub fn id(n: u32) -> BlockId {
+    BlockId::parse(&format!("00000000-0000-4000-8000-{:012}", n)).unwrap()
+}
+
+/// The number `id` was made from.
+pub fn number(id: &BlockId) -> u32 {
+    let id = id.to_string();
+    id[id.len() - 12..].parse().unwrap()
+}
+
+/// Block `n`, of the type `block_type`, with the children `content`.
+pub fn block(n: u32, block_type: &str, content: &[u32]) -> Value {
+    json!({
+        "id": id(n),
+        "version": 1,
+        "type": block_type,
+        "properties": { "title": [[format!("Block {}", n)]] },
+        "content": content.iter().map(|c| id(*c)).collect::<Vec<_>>(),
+        "created_time": 0,
+        "last_edited_time": 0,
+        "parent_id": id(0),
+        "parent_table": "block",
+        "alive": true,
+        "created_by_table": "notion_user",
+        "created_by_id": "",
+        "last_edited_by_table": "notion_user",
+        "last_edited_by_id": ""
+    })
+}
+
+/// A `loadPageChunk` response holding `blocks`, with the role `reader` on each.
+pub fn chunk(blocks: Vec<Value>) -> String {
+    let mut table = Map::new();
+    for value in blocks {
+        let id = value["id"].as_str().unwrap().to_string();
+        table.insert(id, json!({ "role": "reader", "value": value }));
+    }
+    json!({ "recordMap": { "block": table } }).to_string()
+}
+
+/// What a block's `BlockContext` held when it was rendered.
+#[derive(Debug, PartialEq)]
+pub struct Visit {
+    pub block: u32,
+    pub parent: Option<u32>,
+    pub depth: usize,
+    pub index: usize,
+}
+
+/// Renders each block as its number, followed by its children in brackets (eg. `1[2 3]`), and
+/// lists as `ul[...]` or `ol[...]`. Numbered lists that don't start at 1, or are nested, also get
+/// their first item's position (eg. `ol3.1[...]`). Text is left out. Keeps a `Visit` for every
+/// block it renders.
+#[derive(Clone, Default)]
+pub struct Outline {
+    pub visits: Arc<Mutex<Vec<Visit>>>,
+}
+
+impl Outline {
+    fn block(&self, context: &BlockContext, children: String) -> String {
+        self.visits.lock().unwrap().push(Visit {
+            block: number(&context.id),
+            parent: context.parent.map(|parent| number(&parent.id)),
+            depth: context.depth,
+            index: context.index,
+        });
+
+        let n = number(&context.id);
+        if children.is_empty() {
+            n.to_string()
+        } else {
+            format!("{}[{}]", n, children)
+        }
+    }
+}
+
+impl BlockRenderer<String> for Outline {
+    fn page_block(&self, context: &BlockContext, children: String, _: Option<String>) -> String {
+        self.block(context, children)
+    }
+
+    fn text_block(&self, context: &BlockContext, children: String, _: Option<String>) -> String {
+        self.block(context, children)
+    }
+
+    fn bulleted_list_block(
+        &self,
+        context: &BlockContext,
+        _: ListPosition,
+        children: String,
+        _: Option<String>,
+    ) -> String {
+        self.block(context, children)
+    }
+
+    fn numbered_list_block(
+        &self,
+        context: &BlockContext,
+        _: ListPosition,
+        children: String,
+        _: Option<String>,
+    ) -> String {
+        self.block(context, children)
+    }
+
+    fn toggle_block(&self, context: &BlockContext, children: String, _: Option<String>) -> String {
+        self.block(context, children)
+    }
+
+    fn quote_block(&self, context: &BlockContext, children: String, _: Option<String>) -> String {
+        self.block(context, children)
+    }
+
+    fn header_block(&self, context: &BlockContext, children: String, _: Option<String>) -> String {
+        self.block(context, children)
+    }
+
+    fn sub_header_block(
+        &self,
+        context: &BlockContext,
+        children: String,
+        _: Option<String>,
+    ) -> String {
+        self.block(context, children)
+    }
+
+    fn sub_sub_header_block(
+        &self,
+        context: &BlockContext,
+        children: String,
+        _: Option<String>,
+    ) -> String {
+        self.block(context, children)
+    }
+
+    fn divider_block(&self, context: &BlockContext, children: String) -> String {
+        self.block(context, children)
+    }
+
+    fn unknown_block(&self, context: &BlockContext, children: String, _: &str) -> String {
+        self.block(context, children)
+    }
+
+    fn empty(&self) -> String {
+        String::new()
+    }
+}
+
+impl InlineRenderer<String> for Outline {
+    fn text(&self, _: &str) -> String {
+        String::new()
+    }
+
+    fn bold(&self, acc: String) -> String {
+        acc
+    }
+
+    fn italic(&self, acc: String) -> String {
+        acc
+    }
+
+    fn underline(&self, acc: String) -> String {
+        acc
+    }
+
+    fn strike(&self, acc: String) -> String {
+        acc
+    }
+
+    fn link(&self, acc: String, _: &str) -> String {
+        acc
+    }
+
+    fn code(&self, acc: String) -> String {
+        acc
+    }
+
+    fn highlight(&self, acc: String, _: &ColorType) -> String {
+        acc
+    }
+}
+
+impl WrapperRenderer<String> for Outline {
+    fn bulleted_list_wrapper(&self, items: Vec<String>, _: ListPosition) -> String {
+        format!("ul[{}]", items.join(" "))
+    }
+
+    fn numbered_list_wrapper(&self, items: Vec<String>, first: ListPosition) -> String {
+        if first == ListPosition::default() {
+            format!("ol[{}]", items.join(" "))
+        } else {
+            format!("ol{}.{}[{}]", first.ordinal, first.level, items.join(" "))
+        }
+    }
+
+    fn collect(&self, items: Vec<String>) -> String {
+        let items: Vec<_> = items.into_iter().filter(|item| !item.is_empty()).collect();
+        items.join(" ")
+    }
+}
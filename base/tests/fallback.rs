@@ -0,0 +1,82 @@
+use base::id::BlockId;
+use base::parser::{parse, Either};
+use serde_json::{json, Value};
+
+const GOOD: &str = "4b5e2a55-0ae0-4f4e-a1c7-1a2b3c4d5e6f";
+const BAD: &str = "9f1c0b7e-3d1a-4c52-8e8f-6a7b8c9d0e1f";
+
+fn block(id: &str) -> Value {
+    json!({
+        "id": id,
+        "version": 3,
+        "type": "text",
+        "properties": { "title": [["Hello"]] },
+        "content": [],
+        "created_time": 1591012800000u64,
+        "last_edited_time": 1593604800000u64,
+        "parent_id": GOOD,
+        "parent_table": "block",
+        "alive": true,
+        "created_by_table": "notion_user",
+        "created_by_id": "ee5f0f84-409a-440f-983a-a5315961c6e4",
+        "last_edited_by_table": "notion_user",
+        "last_edited_by_id": "ee5f0f84-409a-440f-983a-a5315961c6e4"
+    })
+}
+
+/// Parses a chunk holding a good block and one with `field` set to `value`, and checks that only
+/// the second one falls back to its JSON, unchanged.
+fn falls_back(field: &str, value: Value) {
+    let mut bad = block(BAD);
+    bad[field] = value;
+    let input = json!({
+        "recordMap": {
+            "block": {
+                GOOD: { "role": "reader", "value": block(GOOD) },
+                BAD: { "role": "reader", "value": bad }
+            }
+        }
+    })
+    .to_string();
+
+    let data = parse(&input).unwrap();
+    let block = |id: &str| &data.record_map.block[&BlockId::parse(id).unwrap()].value;
+    assert!(matches!(block(GOOD), Either::Left(_)));
+    match block(BAD) {
+        Either::Right(raw) => assert_eq!(raw, &bad),
+        Either::Left(_) => panic!("`{}` should have made the block fall back", field),
+    }
+}
+
+#[test]
+fn falls_back_on_bad_text() {
+    falls_back("properties", json!({ "title": [[1]] }));
+}
+
+#[test]
+fn falls_back_on_unknown_text_properties() {
+    falls_back("properties", json!({ "other": [["x"]] }));
+}
+
+#[test]
+fn falls_back_on_bad_ids() {
+    falls_back("content", json!(["not-an-id"]));
+}
+
+#[test]
+fn falls_back_on_bad_numbers() {
+    falls_back("version", json!("x"));
+}
+
+#[test]
+fn falls_back_on_bad_formats() {
+    falls_back(
+        "properties",
+        json!({ "title": [["Hello"], ["world", [["b"], [1]]], ["!"]] }),
+    );
+}
+
+#[test]
+fn falls_back_on_bad_content() {
+    falls_back("content", json!([GOOD, 1, GOOD]));
+}
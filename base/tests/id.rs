@@ -0,0 +1,111 @@
+use base::id::BlockId;
+
+const ID: &str = "ddda599f-ff69-4974-9dec-86f6abf3209a";
+const OTHER_ID: &str = "3f8a2b4c-7d1e-4f5a-9b6c-0d2e4f6a8b1c";
+
+fn parse(input: &str) -> String {
+    match BlockId::parse(input) {
+        Ok(id) => id.to_string(),
+        Err(error) => panic!("couldn't parse {:?}: {}", input, error),
+    }
+}
+
+#[test]
+fn parses_raw_and_dashed_ids() {
+    assert_eq!(parse("ddda599fff6949749dec86f6abf3209a"), ID);
+    assert_eq!(parse(ID), ID);
+    assert_eq!(parse("DDDA599F-FF69-4974-9DEC-86F6ABF3209A"), ID);
+    assert_eq!(parse("DdDa599fFF6949749DEC86f6abf3209a"), ID);
+    assert_eq!(parse("  ddda599fff6949749dec86f6abf3209a\n"), ID);
+    assert_eq!(
+        BlockId::parse("ddda599fff6949749dec86f6abf3209a").unwrap(),
+        BlockId::parse(ID).unwrap()
+    );
+}
+
+#[test]
+fn parses_notion_urls() {
+    for url in [
+        "https://www.notion.so/ddda599fff6949749dec86f6abf3209a",
+        "https://www.notion.so/My-Page-ddda599fff6949749dec86f6abf3209a",
+        "https://www.notion.so/workspace/My-Page-ddda599fff6949749dec86f6abf3209a",
+        "https://www.notion.so/My-Page-DDDA599FFF6949749DEC86F6ABF3209A",
+        "notion.so/My-Page-ddda599fff6949749dec86f6abf3209a",
+        "https://acme.notion.site/Public-Page-ddda599fff6949749dec86f6abf3209a",
+        "https://docs.example.com/guides/Getting-Started-ddda599fff6949749dec86f6abf3209a",
+        "https://docs.example.com/ddda599f-ff69-4974-9dec-86f6abf3209a",
+    ] {
+        assert_eq!(parse(url), ID, "{}", url);
+    }
+}
+
+#[test]
+fn ignores_queries_and_fragments() {
+    for url in [
+        "https://www.notion.so/My-Page-ddda599fff6949749dec86f6abf3209a?pvs=4",
+        "https://www.notion.so/My-Page-ddda599fff6949749dec86f6abf3209a/",
+        "https://www.notion.so/My-Page-ddda599fff6949749dec86f6abf3209a#3f8a2b4c7d1e4f5a9b6c0d2e4f6a8b1c",
+        "https://www.notion.so/My-Page-ddda599fff6949749dec86f6abf3209a?v=3f8a2b4c7d1e4f5a9b6c0d2e4f6a8b1c",
+    ] {
+        assert_eq!(parse(url), ID, "{}", url);
+    }
+}
+
+#[test]
+fn prefers_the_side_peek_page() {
+    assert_eq!(
+        parse("https://www.notion.so/Tasks-ddda599fff6949749dec86f6abf3209a?v=0123&p=3f8a2b4c7d1e4f5a9b6c0d2e4f6a8b1c&pm=s"),
+        OTHER_ID
+    );
+    assert_eq!(
+        parse("https://acme.notion.site/Tasks-ddda599fff6949749dec86f6abf3209a?p=3f8a2b4c7d1e4f5a9b6c0d2e4f6a8b1c"),
+        OTHER_ID
+    );
+    // A `p` that isn't an ID is skipped.
+    assert_eq!(
+        parse("https://www.notion.so/Tasks-ddda599fff6949749dec86f6abf3209a?p=1"),
+        ID
+    );
+}
+
+#[test]
+fn rejects_inputs_without_an_id() {
+    for input in [
+        "",
+        "not an id",
+        "ddda599fff6949749dec86f6abf3209",
+        "0ddda599fff6949749dec86f6abf3209a",
+        "ddda599f-ff69-4974-9dec-86f6abf3209",
+        "ddda599fff69-4974-9dec-86f6abf3209a-",
+        "gdda599fff6949749dec86f6abf3209a",
+        "https://www.notion.so/",
+        "https://www.notion.so/My-Page",
+        "https://www.notion.so/My-Page-0ddda599fff6949749dec86f6abf3209a",
+        "https://www.notion.so/My-Pageddda599fff6949749dec86f6abf3209a",
+        "https://ddda599fff6949749dec86f6abf3209a.example.com/",
+        "https://www.notion.so/My-Page-ddda599fff6949749dec86f6abf3209a/comments",
+    ] {
+        assert!(
+            BlockId::parse(input).is_err(),
+            "{:?} should be rejected",
+            input
+        );
+    }
+}
+
+#[test]
+fn round_trips_through_serde() {
+    let id = BlockId::parse(ID).unwrap();
+    let json = serde_json::to_string(&id).unwrap();
+    assert_eq!(json, format!("\"{}\"", ID));
+    assert_eq!(serde_json::from_str::<BlockId>(&json).unwrap(), id);
+    assert_eq!(
+        serde_json::from_str::<BlockId>("\"ddda599fff6949749dec86f6abf3209a\"").unwrap(),
+        id
+    );
+    // URLs are only accepted by `parse`.
+    assert!(serde_json::from_str::<BlockId>(
+        "\"https://www.notion.so/ddda599fff6949749dec86f6abf3209a\""
+    )
+    .is_err());
+}
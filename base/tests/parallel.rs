@@ -0,0 +1,101 @@
+#![cfg(feature = "rayon")]
+
+mod common;
+
+use base::diagnostics::Diagnostic;
+use base::parser::parse;
+use base::renderer::Renderer;
+use common::{block, chunk, id, Outline};
+use serde_json::Value;
+
+/// Adds block `n` of the type `block_type`, with `levels` levels of numbered items nested under it,
+/// and returns the next free number.
+fn nested(blocks: &mut Vec<Value>, n: u32, block_type: &str, levels: u32) -> u32 {
+    if levels == 0 {
+        blocks.push(block(n, block_type, &[]));
+        return n + 1;
+    }
+    // Two children, so the nested list has a run to group.
+    let first = n + 1;
+    let second = nested(blocks, first, "numbered_list", levels - 1);
+    let next = nested(blocks, second, "numbered_list", levels - 1);
+    blocks.push(block(n, block_type, &[first, second]));
+    next
+}
+
+/// A long page, block 0: runs of numbered and bulleted items broken up by other blocks (so the
+/// numbering carries on after an interruption), numbered lists nested a few levels deep, and
+/// blocks that can't be rendered (of an unknown type, ones that don't parse, and missing ones).
+fn page() -> String {
+    const PATTERN: [&str; 9] = [
+        "numbered_list",
+        "numbered_list",
+        "text",
+        "numbered_list",
+        "bulleted_list",
+        "bulleted_list",
+        "numbered_list",
+        "callout",
+        "to_do",
+    ];
+
+    let mut blocks = vec![];
+    let mut children = vec![];
+    let mut n = 1;
+    for i in 0..600 {
+        children.push(n);
+        let levels = match i % 4 {
+            0 => 3,
+            1 => 1,
+            _ => 0,
+        };
+        n = nested(&mut blocks, n, PATTERN[i % PATTERN.len()], levels);
+        if i % 50 == 0 {
+            // Listed, but not in the block table.
+            children.push(n);
+            n += 1;
+        }
+    }
+    blocks.push(block(0, "page", &children));
+    chunk(blocks)
+}
+
+/// Diagnostics in a set order, since the ones from `render_parallel` can come in any order.
+fn sorted(mut diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+    diagnostics.sort_by_key(|diagnostic| diagnostic.to_string());
+    diagnostics
+}
+
+#[test]
+fn renders_the_same_in_parallel() {
+    let input = page();
+    let data = parse(&input).unwrap();
+    let outline = Outline::default();
+    let renderer = Renderer::new(
+        &data.record_map.block,
+        outline.clone(),
+        outline.clone(),
+        outline,
+    );
+
+    let expected = renderer.render(&id(0));
+    let expected_diagnostics = sorted(renderer.take_diagnostics());
+    let result = renderer.render_parallel(&id(0));
+    let diagnostics = sorted(renderer.take_diagnostics());
+
+    // Make sure the page has what it's meant to test.
+    assert!(
+        expected.contains("ol3.0["),
+        "numbering doesn't carry on: {}",
+        expected
+    );
+    assert!(
+        expected.contains("ol1.2["),
+        "lists aren't nested: {}",
+        expected
+    );
+    assert!(expected_diagnostics.len() > 100);
+
+    assert_eq!(result, expected);
+    assert_eq!(diagnostics, expected_diagnostics);
+}
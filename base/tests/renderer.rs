@@ -0,0 +1,310 @@
+mod common;
+
+use base::diagnostics::{Diagnostic, DiagnosticReason};
+use base::parser::{parse, BaseValueType, PagePermissions};
+use base::renderer::{PolicyDecision, RenderPolicy, Renderer};
+use common::{block, chunk, id, number, Outline, Visit};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+/// What a `RenderPolicy` was asked about a block: its number, the role on it, and the users in
+/// its page's permissions.
+type Check = (u32, String, Vec<String>);
+
+/// Redacts the block `redact`, and keeps track of what it was asked.
+#[derive(Clone, Default)]
+struct Policy {
+    redact: Option<u32>,
+    checks: Arc<Mutex<Vec<Check>>>,
+}
+
+impl Policy {
+    fn checks(&self) -> Vec<Check> {
+        self.checks.lock().unwrap().clone()
+    }
+}
+
+impl RenderPolicy for Policy {
+    fn check(
+        &self,
+        block: &BaseValueType,
+        role: &str,
+        page_permissions: &[PagePermissions],
+    ) -> PolicyDecision {
+        let n = number(&block.id);
+        self.checks.lock().unwrap().push((
+            n,
+            role.to_string(),
+            page_permissions
+                .iter()
+                .filter_map(|permission| permission.user_id.clone())
+                .collect(),
+        ));
+        if self.redact == Some(n) {
+            PolicyDecision::Redact
+        } else {
+            PolicyDecision::Render
+        }
+    }
+}
+
+/// Page `n`, shared with `user` as a reader.
+fn page(n: u32, content: &[u32], user: &str) -> Value {
+    let mut page = block(n, "page", content);
+    page["permissions"] = json!([{ "role": "reader", "type": "user_permission", "user_id": user }]);
+    page
+}
+
+fn render_with(input: &str, policy: Option<Policy>) -> (String, Vec<Diagnostic>) {
+    let data = parse(input).unwrap();
+    let outline = Outline::default();
+    let mut renderer = Renderer::new(
+        &data.record_map.block,
+        outline.clone(),
+        outline.clone(),
+        outline,
+    );
+    if let Some(policy) = policy {
+        renderer = renderer.policy(policy);
+    }
+    let output = renderer.render(&id(0));
+    (output, renderer.take_diagnostics())
+}
+
+#[test]
+fn reports_blocks_it_cant_render() {
+    let mut unparseable = block(2, "text", &[]);
+    unparseable["version"] = "x".into();
+    let input = chunk(vec![
+        block(0, "page", &[1, 2, 3, 4]),
+        unparseable,
+        block(3, "column_list", &[]),
+        block(4, "callout", &[]),
+    ]);
+    let data = parse(&input).unwrap();
+    let outline = Outline::default();
+    let renderer = Renderer::new(
+        &data.record_map.block,
+        outline.clone(),
+        outline.clone(),
+        outline,
+    );
+
+    renderer.render(&id(0));
+    assert_eq!(
+        renderer.take_diagnostics(),
+        vec![
+            Diagnostic {
+                block_id: id(1),
+                block_type: None,
+                reason: DiagnosticReason::MissingFromTable,
+            },
+            Diagnostic {
+                block_id: id(2),
+                block_type: Some(String::from("text")),
+                reason: DiagnosticReason::ParseFailure(String::from(
+                    "invalid value for field `version`: invalid type: string \"x\", expected i64"
+                )),
+            },
+            Diagnostic {
+                block_id: id(3),
+                block_type: Some(String::from("column_list")),
+                reason: DiagnosticReason::UnsupportedType,
+            },
+            Diagnostic {
+                block_id: id(4),
+                block_type: Some(String::from("callout")),
+                reason: DiagnosticReason::UnsupportedType,
+            },
+        ]
+    );
+    assert!(renderer.take_diagnostics().is_empty());
+}
+
+#[test]
+fn skips_dead_blocks_unless_asked_not_to() {
+    let mut dead = block(2, "text", &[3]);
+    dead["alive"] = false.into();
+    let input = chunk(vec![
+        block(0, "page", &[1, 2, 4]),
+        block(1, "text", &[]),
+        dead,
+        block(3, "text", &[]),
+        block(4, "text", &[]),
+    ]);
+    let data = parse(&input).unwrap();
+    let outline = Outline::default();
+
+    let renderer = Renderer::new(
+        &data.record_map.block,
+        outline.clone(),
+        outline.clone(),
+        outline.clone(),
+    );
+    assert_eq!(renderer.render(&id(0)), "0[1 4]");
+    assert_eq!(
+        renderer.take_diagnostics(),
+        vec![Diagnostic {
+            block_id: id(2),
+            block_type: Some(String::from("text")),
+            reason: DiagnosticReason::Deleted,
+        }]
+    );
+
+    let renderer = Renderer::new(
+        &data.record_map.block,
+        outline.clone(),
+        outline.clone(),
+        outline,
+    )
+    .include_dead_blocks(true);
+    assert_eq!(renderer.render(&id(0)), "0[1 2[3] 4]");
+    assert!(renderer.take_diagnostics().is_empty());
+}
+
+#[test]
+fn skips_blocks_without_access() {
+    let mut input: Value = serde_json::from_str(&chunk(vec![
+        block(0, "page", &[1, 2, 4]),
+        block(1, "text", &[]),
+        block(2, "toggle", &[3]),
+        block(3, "text", &[]),
+        block(4, "text", &[]),
+    ]))
+    .unwrap();
+    input["recordMap"]["block"][id(2).to_string()]["role"] = "none".into();
+
+    let policy = Policy::default();
+    let (output, diagnostics) = render_with(&input.to_string(), Some(policy.clone()));
+    assert_eq!(output, "0[1 4]");
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic {
+            block_id: id(2),
+            block_type: Some(String::from("toggle")),
+            reason: DiagnosticReason::NoAccess,
+        }]
+    );
+    // Blocks without access never get to the policy.
+    let checked: Vec<u32> = policy.checks().into_iter().map(|(n, ..)| n).collect();
+    assert_eq!(checked, vec![0, 1, 4]);
+}
+
+#[test]
+fn redacts_blocks_and_their_children() {
+    let mut nested = block(3, "text", &[]);
+    nested["parent_id"] = id(2).to_string().into();
+    let input = chunk(vec![
+        block(0, "page", &[1, 2, 4]),
+        block(1, "text", &[]),
+        block(2, "toggle", &[3]),
+        nested,
+        block(4, "text", &[]),
+    ]);
+
+    let policy = Policy {
+        redact: Some(2),
+        ..Policy::default()
+    };
+    let (output, diagnostics) = render_with(&input, Some(policy.clone()));
+    assert_eq!(output, "0[1 4]");
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic {
+            block_id: id(2),
+            block_type: Some(String::from("toggle")),
+            reason: DiagnosticReason::Redacted,
+        }]
+    );
+    let checked: Vec<u32> = policy.checks().into_iter().map(|(n, ..)| n).collect();
+    assert_eq!(checked, vec![0, 1, 2, 4]);
+}
+
+#[test]
+fn checks_blocks_against_their_nearest_page() {
+    let mut subpage = page(2, &[3], "b");
+    subpage["parent_id"] = id(1).to_string().into();
+    let mut nested = block(3, "text", &[]);
+    nested["parent_id"] = id(2).to_string().into();
+    let input = chunk(vec![
+        page(0, &[1], "a"),
+        block(1, "toggle", &[2]),
+        subpage,
+        nested,
+    ]);
+
+    let policy = Policy::default();
+    let (output, diagnostics) = render_with(&input, Some(policy.clone()));
+    assert_eq!(output, "0[1[2[3]]]");
+    assert!(diagnostics.is_empty());
+    let reader = String::from("reader");
+    assert_eq!(
+        policy.checks(),
+        vec![
+            (0, reader.clone(), vec![String::from("a")]),
+            (1, reader.clone(), vec![String::from("a")]),
+            (2, reader.clone(), vec![String::from("b")]),
+            (3, reader, vec![String::from("b")]),
+        ]
+    );
+}
+
+#[test]
+fn stops_at_parent_cycles() {
+    // Blocks 0 and 1 are each other's parents, and neither is a page with permissions.
+    let mut top = block(0, "toggle", &[1]);
+    top["parent_id"] = id(1).to_string().into();
+    let input = chunk(vec![top, block(1, "text", &[])]);
+
+    let policy = Policy::default();
+    let (output, _) = render_with(&input, Some(policy.clone()));
+    assert_eq!(output, "0[1]");
+    let reader = String::from("reader");
+    assert_eq!(
+        policy.checks(),
+        vec![(0, reader.clone(), vec![]), (1, reader, vec![])]
+    );
+}
+
+#[test]
+fn gives_blocks_their_place_in_the_tree() {
+    // Block 2 is listed, but missing from the block table.
+    let input = chunk(vec![
+        block(0, "page", &[1, 2, 6]),
+        block(1, "toggle", &[3]),
+        block(3, "toggle", &[4, 5]),
+        block(4, "text", &[]),
+        block(5, "text", &[]),
+        block(6, "text", &[]),
+    ]);
+    let data = parse(&input).unwrap();
+    let outline = Outline::default();
+    let renderer = Renderer::new(
+        &data.record_map.block,
+        outline.clone(),
+        outline.clone(),
+        outline.clone(),
+    );
+
+    assert_eq!(renderer.render(&id(0)), "0[1[3[4 5]] 6]");
+    let visit = |block, parent, depth, index| Visit {
+        block,
+        parent,
+        depth,
+        index,
+    };
+    let mut visits = outline.visits.lock().unwrap().split_off(0);
+    visits.sort_by_key(|visit| visit.block);
+    assert_eq!(
+        visits,
+        vec![
+            visit(0, None, 0, 0),
+            visit(1, Some(0), 1, 0),
+            visit(3, Some(1), 2, 0),
+            visit(4, Some(3), 3, 0),
+            visit(5, Some(3), 3, 1),
+            // The missing block 2 doesn't count.
+            visit(6, Some(0), 1, 1),
+        ]
+    );
+}
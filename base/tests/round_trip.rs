@@ -0,0 +1,130 @@
+use base::parser::{
+    parse, parse_owned, parse_slice, parse_stream, ColorType, ContextFormat, Either, FormatType,
+    FormattedText, NoContextFormat, RootBlockType,
+};
+use futures_util::future::FutureExt;
+use futures_util::stream;
+use serde_json::Value;
+use std::fs;
+
+fn fixture(name: &str) -> String {
+    fs::read_to_string(format!("fixtures/round_trip/{}", name)).unwrap()
+}
+
+fn json(input: &str) -> Value {
+    serde_json::from_str(input).unwrap()
+}
+
+#[test]
+fn round_trips_rich_text() {
+    let input = fixture("rich_text.json");
+    let text: Vec<FormattedText> = serde_json::from_str(&input).unwrap();
+    assert_eq!(serde_json::to_value(&text).unwrap(), json(&input));
+}
+
+#[test]
+fn keeps_formats_and_colors_it_doesnt_know() {
+    let input = fixture("rich_text.json");
+    let text: Vec<FormattedText> = serde_json::from_str(&input).unwrap();
+    let formats = |index: usize| text[index].formatting.clone().unwrap();
+
+    assert!(matches!(
+        &formats(9)[0],
+        FormatType::Context(ContextFormat::Highlight(ColorType::Unknown(color))) if color == "green"
+    ));
+    assert!(matches!(
+        &formats(8)[0],
+        FormatType::Context(ContextFormat::Highlight(ColorType::None))
+    ));
+    assert!(matches!(
+        &formats(17)[0],
+        FormatType::NoContext(NoContextFormat::Unknown(code)) if code == "zz"
+    ));
+    assert!(matches!(
+        &formats(18)[0],
+        FormatType::NoContext(NoContextFormat::None)
+    ));
+    assert!(matches!(
+        &formats(13)[0],
+        FormatType::Context(ContextFormat::Unknown(code, Value::Object(_))) if code == "d"
+    ));
+}
+
+#[test]
+fn round_trips_blocks() {
+    let input = fixture("page_chunk.json");
+    let data = parse(&input).unwrap();
+
+    // Every block should have parsed, so none of them come back from the raw JSON fallback.
+    for (id, block) in &data.record_map.block {
+        assert!(
+            matches!(block.value, Either::Left(_)),
+            "{} didn't parse",
+            id
+        );
+    }
+    assert_eq!(serde_json::to_value(&data).unwrap(), json(&input));
+}
+
+#[test]
+fn round_trips_owned_blocks() {
+    let input = fixture("page_chunk.json");
+    let data = parse_owned(input.clone()).unwrap();
+    assert_eq!(serde_json::to_value(&data).unwrap(), json(&input));
+}
+
+#[test]
+fn parses_bytes_and_streams_the_same() {
+    let input = fixture("page_chunk.json");
+    let data = parse_slice(input.as_bytes()).unwrap();
+    assert_eq!(serde_json::to_value(&data).unwrap(), json(&input));
+
+    // Chunks split in the middle of strings and multi-byte characters.
+    let chunks = input
+        .as_bytes()
+        .chunks(7)
+        .map(Ok::<_, std::io::Error>)
+        .collect::<Vec<_>>();
+    let data = parse_stream(stream::iter(chunks))
+        .now_or_never()
+        .unwrap()
+        .unwrap();
+    assert_eq!(serde_json::to_value(&data).unwrap(), json(&input));
+}
+
+#[test]
+fn round_trips_unknown_block_types() {
+    let input = fixture("page_chunk.json");
+    let data = parse(&input).unwrap();
+    let callout = data
+        .record_map
+        .block
+        .values()
+        .find_map(|block| match &block.value {
+            Either::Left(value) => match &value.block {
+                RootBlockType::Unknown { type_name, .. } => Some(type_name.clone()),
+                _ => None,
+            },
+            Either::Right(_) => None,
+        });
+    assert_eq!(callout.as_deref(), Some("callout"));
+}
+
+#[test]
+fn keeps_fields_it_doesnt_know() {
+    // A response as Notion sent it, with fields the typings don't hold (eg. `copied_from`,
+    // `ignore_block_count`, `format.block_locked` and extra text properties).
+    let input = fixture("captured_page_chunk.json");
+    let data = parse(&input).unwrap();
+    for (id, block) in &data.record_map.block {
+        assert!(
+            matches!(block.value, Either::Left(_)),
+            "{} didn't parse",
+            id
+        );
+    }
+    assert_eq!(
+        serde_json::to_value(&data.record_map).unwrap(),
+        json(&input)["recordMap"]
+    );
+}
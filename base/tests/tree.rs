@@ -0,0 +1,195 @@
+use base::id::BlockId;
+use base::parser::parse;
+use base::tree::{Document, NodeId};
+use serde_json::{json, Map};
+
+fn id(n: u32) -> BlockId {
+    BlockId::parse(&format!("00000000-0000-4000-8000-{:012}", n)).unwrap()
+}
+
+/// A `loadPageChunk` response holding a block for each `(number, content, alive)`.
+fn chunk(blocks: &[(u32, &[u32], bool)]) -> String {
+    let mut table = Map::new();
+    for (n, content, alive) in blocks {
+        let value = json!({
+            "id": id(*n),
+            "version": 1,
+            "type": "text",
+            "content": content.iter().map(|c| id(*c)).collect::<Vec<_>>(),
+            "created_time": 0,
+            "last_edited_time": 0,
+            "parent_id": id(0),
+            "parent_table": "block",
+            "alive": alive,
+            "created_by_table": "notion_user",
+            "created_by_id": "",
+            "last_edited_by_table": "notion_user",
+            "last_edited_by_id": ""
+        });
+        table.insert(
+            id(*n).to_string(),
+            json!({ "role": "reader", "value": value }),
+        );
+    }
+    json!({ "recordMap": { "block": table } }).to_string()
+}
+
+/// The number of each block's ID, for comparing against.
+fn numbers<I: IntoIterator<Item = NodeId>>(document: &Document, nodes: I) -> Vec<u32> {
+    nodes
+        .into_iter()
+        .map(|node| {
+            let id = document[node].id().to_string();
+            id[id.len() - 12..].parse().unwrap()
+        })
+        .collect()
+}
+
+/// 0
+/// ├── 1
+/// │   ├── 4
+/// │   │   └── 6
+/// │   └── 5
+/// ├── 2
+/// └── 3
+///     └── 7
+fn tree() -> String {
+    chunk(&[
+        (0, &[1, 2, 3], true),
+        (1, &[4, 5], true),
+        (2, &[], true),
+        (3, &[7], true),
+        (4, &[6], true),
+        (5, &[], true),
+        (6, &[], true),
+        (7, &[], true),
+    ])
+}
+
+fn node(document: &Document, n: u32) -> NodeId {
+    document.find(&id(n)).unwrap()
+}
+
+#[test]
+fn walks_blocks_in_pre_order() {
+    let input = tree();
+    let document = Document::new(parse(&input).unwrap().record_map, &id(0)).unwrap();
+    assert_eq!(document.len(), 8);
+    assert_eq!(
+        numbers(&document, document.iter()),
+        [0, 1, 4, 6, 5, 2, 3, 7]
+    );
+    assert_eq!(
+        numbers(&document, document.pre_order(node(&document, 1))),
+        [1, 4, 6, 5]
+    );
+    assert_eq!(
+        numbers(&document, document.pre_order(node(&document, 7))),
+        [7]
+    );
+}
+
+#[test]
+fn finds_children_and_ancestors() {
+    let input = tree();
+    let document = Document::new(parse(&input).unwrap().record_map, &id(0)).unwrap();
+    let root = document.root();
+
+    assert_eq!(numbers(&document, document.children(root)), [1, 2, 3]);
+    assert_eq!(numbers(&document, document.children(root).rev()), [3, 2, 1]);
+    assert_eq!(document.children(root).len(), 3);
+    assert_eq!(document.children(node(&document, 6)).len(), 0);
+
+    assert_eq!(
+        numbers(&document, document.ancestors(node(&document, 6))),
+        [4, 1, 0]
+    );
+    assert_eq!(document.ancestors(root).count(), 0);
+    assert_eq!(
+        document.parent(node(&document, 7)),
+        Some(node(&document, 3))
+    );
+    assert_eq!(document.parent(root), None);
+}
+
+#[test]
+fn finds_siblings() {
+    let input = tree();
+    let document = Document::new(parse(&input).unwrap().record_map, &id(0)).unwrap();
+    let sibling = |n, next: bool| {
+        let node = node(&document, n);
+        let sibling = if next {
+            document.next_sibling(node)
+        } else {
+            document.previous_sibling(node)
+        };
+        numbers(&document, sibling)
+    };
+
+    assert_eq!(sibling(1, true), [2]);
+    assert_eq!(sibling(2, true), [3]);
+    assert_eq!(sibling(3, true), Vec::<u32>::new());
+    assert_eq!(sibling(3, false), [2]);
+    assert_eq!(sibling(1, false), Vec::<u32>::new());
+    // Only children.
+    assert_eq!(sibling(6, true), Vec::<u32>::new());
+    assert_eq!(sibling(6, false), Vec::<u32>::new());
+    // The root.
+    assert_eq!(sibling(0, true), Vec::<u32>::new());
+    assert_eq!(sibling(0, false), Vec::<u32>::new());
+}
+
+#[test]
+fn counts_depth() {
+    let input = tree();
+    let document = Document::new(parse(&input).unwrap().record_map, &id(0)).unwrap();
+    let depths: Vec<usize> = document.iter().map(|node| document[node].depth).collect();
+    assert_eq!(depths, [0, 1, 2, 3, 2, 1, 1, 2]);
+}
+
+#[test]
+fn drops_cycles_and_duplicates() {
+    // 3 is listed under 1 and 2 (and twice under 1), 1 lists the root, 3 lists 1, and 9 is
+    // missing.
+    let input = chunk(&[
+        (0, &[1, 2, 9], true),
+        (1, &[3, 3, 0], true),
+        (2, &[3, 1], true),
+        (3, &[1], true),
+    ]);
+    let document = Document::new(parse(&input).unwrap().record_map, &id(0)).unwrap();
+
+    assert_eq!(document.len(), 4);
+    assert_eq!(numbers(&document, document.iter()), [0, 1, 3, 2]);
+    assert_eq!(
+        document.parent(node(&document, 3)),
+        Some(node(&document, 1))
+    );
+    assert_eq!(document.children(node(&document, 2)).len(), 0);
+    assert_eq!(document.find(&id(9)), None);
+}
+
+#[test]
+fn prunes_dead_subtrees() {
+    // 1 was deleted, but the blocks under it weren't.
+    let input = chunk(&[
+        (0, &[1, 2], true),
+        (1, &[3], false),
+        (2, &[], true),
+        (3, &[4], true),
+        (4, &[], true),
+    ]);
+
+    let document = Document::new(parse(&input).unwrap().record_map, &id(0)).unwrap();
+    assert_eq!(numbers(&document, document.iter()), [0, 1, 3, 4, 2]);
+
+    let document = Document::without_dead(parse(&input).unwrap().record_map, &id(0)).unwrap();
+    assert_eq!(numbers(&document, document.iter()), [0, 2]);
+    assert_eq!(document.find(&id(3)), None);
+    assert_eq!(document.find(&id(4)), None);
+    assert_eq!(document.next_sibling(node(&document, 2)), None);
+    assert_eq!(document.previous_sibling(node(&document, 2)), None);
+
+    let input = chunk(&[(0, &[], false)]);
+    assert!(Document::without_dead(parse(&input).unwrap().record_map, &id(0)).is_err());
+}
@@ -0,0 +1,33 @@
+use anyhow::Result;
+
+/// A database exported as CSV. The first column holds the title of each row.
+pub struct Database {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Database {
+    /// The title of each row, in the order they appear in the CSV.
+    pub fn titles(&self) -> impl Iterator<Item = &str> {
+        self.rows
+            .iter()
+            .map(|row| row.first().map(String::as_str).unwrap_or(""))
+    }
+}
+
+/// Parses a database exported as CSV.
+pub fn parse_database(csv: &str) -> Result<Database> {
+    // Notion starts its CSVs with a byte order mark.
+    let csv = csv.trim_start_matches('\u{feff}');
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(csv.as_bytes());
+
+    let columns = reader.headers()?.iter().map(String::from).collect();
+    let rows = reader
+        .records()
+        .map(|record| Ok(record?.iter().map(String::from).collect()))
+        .collect::<Result<_>>()?;
+
+    Ok(Database { columns, rows })
+}
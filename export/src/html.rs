@@ -0,0 +1,665 @@
+use crate::page::{
+    block, image, is_page_file, page, plain_text, property, text_properties, Node, ParsedPage,
+};
+use crate::resolve_path;
+use base::id::BlockId;
+use base::parser::{
+    from_color, ContextFormat, FormatType, FormattedText, NoContextFormat, RootBlockType,
+    TodoProperties, YesOrNo,
+};
+use std::borrow::Cow;
+
+/// Elements that have no end tag.
+const VOID: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// Elements whose contents aren't HTML, and are skipped.
+const RAW_TEXT: [&str; 2] = ["script", "style"];
+
+/// Elements that hold blocks, rather than text.
+const BLOCKS: [&str; 20] = [
+    "article",
+    "blockquote",
+    "details",
+    "div",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "ol",
+    "p",
+    "pre",
+    "summary",
+    "table",
+    "ul",
+    "img",
+];
+
+/// An element in a page's HTML.
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Content>,
+}
+
+enum Content {
+    Element(Element),
+    Text(String),
+}
+
+impl Element {
+    fn new(name: String) -> Self {
+        Element {
+            name,
+            attributes: vec![],
+            children: vec![],
+        }
+    }
+
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn has_class(&self, class: &str) -> bool {
+        self.attribute("class")
+            .is_some_and(|classes| classes.split_whitespace().any(|c| c == class))
+    }
+
+    fn is_block(&self) -> bool {
+        BLOCKS.contains(&self.name.as_str())
+    }
+
+    /// The element's child elements.
+    fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|child| match child {
+            Content::Element(element) => Some(element),
+            Content::Text(_) => None,
+        })
+    }
+
+    /// The first element under this one (in document order) that matches `predicate`.
+    fn find(&self, predicate: &dyn Fn(&Element) -> bool) -> Option<&Element> {
+        self.elements().find_map(|child| {
+            Some(child)
+                .filter(|c| predicate(c))
+                .or_else(|| child.find(predicate))
+        })
+    }
+
+    /// All of the text in the element.
+    fn text(&self) -> String {
+        let mut text = String::new();
+        for child in &self.children {
+            match child {
+                Content::Element(element) => text.push_str(&element.text()),
+                Content::Text(t) => text.push_str(t),
+            }
+        }
+        text
+    }
+
+    /// The block ID Notion puts in the `id` attribute of each block's element.
+    fn block_id(&self) -> Option<BlockId> {
+        self.attribute("id").and_then(|id| BlockId::parse(id).ok())
+    }
+}
+
+/// Decodes the character references in text or an attribute value.
+fn decode(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let reference = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .map(|end| &rest[1..=end]);
+        let character = reference.and_then(|reference| match reference {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'),
+            _ => {
+                let number = reference.strip_prefix('#')?;
+                let code = match number.strip_prefix(|c| c == 'x' || c == 'X') {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => number.parse().ok()?,
+                };
+                char::from_u32(code)
+            }
+        });
+        match (reference, character) {
+            (Some(reference), Some(character)) => {
+                decoded.push(character);
+                rest = &rest[reference.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Reads a start tag's name and attributes. `input` starts after the `<`. Returns the element,
+/// whether it closed itself (`<hr/>`), and the rest of the input.
+fn start_tag(input: &str) -> (Element, bool, &str) {
+    let is_name_end = |c: char| c.is_whitespace() || c == '/' || c == '>';
+    let end = input.find(is_name_end).unwrap_or(input.len());
+    let mut element = Element::new(input[..end].to_ascii_lowercase());
+    let mut rest = &input[end..];
+
+    loop {
+        rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix("/>") {
+            return (element, true, after);
+        } else if let Some(after) = rest.strip_prefix('>') {
+            return (element, false, after);
+        } else if rest.is_empty() {
+            return (element, false, rest);
+        }
+
+        let end = rest
+            .find(|c: char| is_name_end(c) || c == '=')
+            .unwrap_or(rest.len());
+        if end == 0 {
+            // A stray `/`.
+            rest = &rest[1..];
+            continue;
+        }
+        let name = rest[..end].to_ascii_lowercase();
+        rest = rest[end..].trim_start();
+
+        let mut value = String::new();
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            let (raw, after) = match after.chars().next() {
+                Some(quote) if quote == '"' || quote == '\'' => {
+                    let end = after[1..].find(quote).map_or(after.len(), |end| end + 1);
+                    (&after[1..end], after.get(end + 1..).unwrap_or(""))
+                }
+                _ => {
+                    let end = after
+                        .find(|c: char| c.is_whitespace() || c == '>')
+                        .unwrap_or(after.len());
+                    (&after[..end], &after[end..])
+                }
+            };
+            value = decode(raw);
+            rest = after;
+        }
+        element.attributes.push((name, value));
+    }
+}
+
+/// Parses HTML into a tree, under an unnamed root element. Notion's exports are well-formed, so
+/// this doesn't do any of the fixing up browsers do: an end tag closes the elements opened after
+/// its start tag, and end tags without one are skipped.
+fn parse_html(html: &str) -> Element {
+    fn push_text(stack: &mut [Element], text: &str) {
+        if !text.is_empty() {
+            let top = stack.last_mut().unwrap();
+            top.children.push(Content::Text(decode(text)));
+        }
+    }
+
+    fn close(stack: &mut Vec<Element>) {
+        let element = stack.pop().unwrap();
+        let top = stack.last_mut().unwrap();
+        top.children.push(Content::Element(element));
+    }
+
+    let mut stack = vec![Element::new(String::new())];
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        push_text(&mut stack, &rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+        } else if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+        } else if let Some(tag) = rest.strip_prefix("</") {
+            let end = tag.find('>').unwrap_or(tag.len());
+            let name = tag[..end].trim().to_ascii_lowercase();
+            rest = tag.get(end + 1..).unwrap_or("");
+            if let Some(open) = stack.iter().skip(1).rposition(|e| e.name == name) {
+                while stack.len() > open + 1 {
+                    close(&mut stack);
+                }
+            }
+        } else if rest[1..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+            let (element, self_closing, after) = start_tag(&rest[1..]);
+            rest = after;
+            if RAW_TEXT.contains(&element.name.as_str()) {
+                let end_tag = format!("</{}", element.name);
+                rest = rest
+                    .to_ascii_lowercase()
+                    .find(&end_tag)
+                    .map_or("", |end| &rest[end..]);
+            } else if self_closing || VOID.contains(&element.name.as_str()) {
+                let top = stack.last_mut().unwrap();
+                top.children.push(Content::Element(element));
+            } else {
+                stack.push(element);
+            }
+        } else {
+            push_text(&mut stack, "<");
+            rest = &rest[1..];
+        }
+    }
+
+    push_text(&mut stack, rest);
+    while stack.len() > 1 {
+        close(&mut stack);
+    }
+    stack.pop().unwrap()
+}
+
+fn text_block(title: Vec<FormattedText<'static>>) -> RootBlockType<'static> {
+    RootBlockType::Text {
+        properties: text_properties(title),
+    }
+}
+
+/// Turns the elements of a page into blocks.
+struct Converter<'d> {
+    /// The directory of the page's file, which links are relative to.
+    dir: &'d str,
+}
+
+impl<'d> Converter<'d> {
+    /// The path of the page or database in the export that a link goes to, if it goes to one.
+    fn page_path(&self, link: &Element) -> Option<String> {
+        let href = link.attribute("href")?;
+        resolve_path(self.dir, href).filter(|path| is_page_file(path))
+    }
+
+    /// The formatted text in some content, skipping any blocks in it. Whitespace at its ends is
+    /// dropped, like browsers do.
+    fn text(&self, children: &[Content]) -> Vec<FormattedText<'static>> {
+        let mut text = vec![];
+        self.push_text(children, &mut vec![], &mut text);
+
+        if let Some(first) = text.first_mut() {
+            first.text = Cow::Owned(first.text.trim_start().to_string());
+        }
+        if let Some(last) = text.last_mut() {
+            last.text = Cow::Owned(last.text.trim_end().to_string());
+        }
+        text.retain(|run| !run.text.is_empty());
+        text
+    }
+
+    fn push_text(
+        &self,
+        children: &[Content],
+        formats: &mut Vec<FormatType<'static>>,
+        text: &mut Vec<FormattedText<'static>>,
+    ) {
+        for child in children {
+            let element = match child {
+                Content::Text(t) => {
+                    text.push(FormattedText {
+                        text: Cow::Owned(t.clone()),
+                        formatting: if formats.is_empty() {
+                            None
+                        } else {
+                            Some(formats.clone())
+                        },
+                    });
+                    continue;
+                }
+                Content::Element(element) => element,
+            };
+
+            let format = match element.name.as_str() {
+                _ if element.is_block() => continue,
+                "br" => {
+                    text.extend(plain_text("\n"));
+                    continue;
+                }
+                "strong" | "b" => Some(FormatType::NoContext(NoContextFormat::Bold)),
+                "em" | "i" => Some(FormatType::NoContext(NoContextFormat::Italic)),
+                "del" | "s" | "strike" => Some(FormatType::NoContext(NoContextFormat::Strike)),
+                "u" => Some(FormatType::NoContext(NoContextFormat::Underline)),
+                "code" => Some(FormatType::NoContext(NoContextFormat::Code)),
+                // Notion underlines text with a bottom border.
+                "span"
+                    if element
+                        .attribute("style")
+                        .is_some_and(|s| s.contains("border-bottom")) =>
+                {
+                    Some(FormatType::NoContext(NoContextFormat::Underline))
+                }
+                "a" => element.attribute("href").map(|href| {
+                    FormatType::Context(ContextFormat::Link(Cow::Owned(href.to_string())))
+                }),
+                // Colors are written as `<mark class="highlight-red">`.
+                "mark" => element
+                    .attribute("class")
+                    .and_then(|class| {
+                        class
+                            .split_whitespace()
+                            .find_map(|c| c.strip_prefix("highlight-"))
+                    })
+                    .map(|color| FormatType::Context(ContextFormat::Highlight(from_color(color)))),
+                _ => None,
+            };
+
+            let pushed = format.is_some();
+            formats.extend(format);
+            self.push_text(&element.children, formats, text);
+            if pushed {
+                formats.pop();
+            }
+        }
+    }
+
+    /// The blocks in an element that also has text, like a list item.
+    fn child_blocks(&self, element: &Element) -> Vec<Node> {
+        element
+            .children
+            .iter()
+            .filter(|child| matches!(child, Content::Element(e) if e.is_block()))
+            .flat_map(|child| self.blocks(std::slice::from_ref(child)))
+            .collect()
+    }
+
+    fn blocks(&self, children: &[Content]) -> Vec<Node> {
+        let mut nodes = vec![];
+        for child in children {
+            let element = match child {
+                Content::Text(text) if text.trim().is_empty() => continue,
+                Content::Text(text) => {
+                    nodes.push(block(text_block(plain_text(text)), vec![]));
+                    continue;
+                }
+                Content::Element(element) => element,
+            };
+
+            let with_id = |block: RootBlockType<'static>, children| Node::Block {
+                id: element.block_id(),
+                block,
+                children,
+            };
+            match element.name.as_str() {
+                // The page's title and properties, which are read separately.
+                "header" => {}
+                "p" => nodes.push(self.paragraph(element)),
+                "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                    let properties = text_properties(self.text(&element.children));
+                    let heading = match element.name.as_str() {
+                        "h1" => RootBlockType::Header { properties },
+                        "h2" => RootBlockType::SubHeader { properties },
+                        _ => RootBlockType::SubSubHeader { properties },
+                    };
+                    nodes.push(with_id(heading, vec![]));
+                }
+                "blockquote" => {
+                    let properties = text_properties(self.text(&element.children));
+                    nodes.push(with_id(
+                        RootBlockType::Quote { properties },
+                        self.child_blocks(element),
+                    ));
+                }
+                "hr" => nodes.push(with_id(RootBlockType::Divider, vec![])),
+                "pre" => {
+                    let code = element.text();
+                    let title = vec![FormattedText {
+                        text: Cow::Owned(code.trim_end_matches('\n').to_string()),
+                        formatting: Some(vec![FormatType::NoContext(NoContextFormat::Code)]),
+                    }];
+                    nodes.push(with_id(text_block(title), vec![]));
+                }
+                "ul" | "ol" => {
+                    let items: Vec<&Element> =
+                        element.elements().filter(|e| e.name == "li").collect();
+                    // Notion writes each item as a list of its own, with the block's ID on the list.
+                    let list_id = if items.len() == 1 {
+                        element.block_id()
+                    } else {
+                        None
+                    };
+                    for item in items {
+                        nodes.push(self.item(element, item, item.block_id().or(list_id)));
+                    }
+                }
+                "details" => nodes.push(self.toggle(element, element.block_id())),
+                "figure" if element.has_class("image") => {
+                    let img = element.find(&|e| e.name == "img");
+                    let source = img
+                        .and_then(|img| img.attribute("src"))
+                        .or_else(|| {
+                            element
+                                .find(&|e| e.name == "a")
+                                .and_then(|a| a.attribute("href"))
+                        })
+                        .unwrap_or("");
+                    let caption = match element.find(&|e| e.name == "figcaption") {
+                        Some(caption) => caption.text(),
+                        None => img
+                            .and_then(|img| img.attribute("alt"))
+                            .unwrap_or("")
+                            .to_string(),
+                    };
+                    nodes.push(with_id(image(self.image_source(source), caption), vec![]));
+                }
+                "img" => {
+                    let source = element.attribute("src").unwrap_or("");
+                    let alt = element.attribute("alt").unwrap_or("").to_string();
+                    nodes.push(with_id(image(self.image_source(source), alt), vec![]));
+                }
+                "figure" if element.has_class("link-to-page") => {
+                    let link = element.find(&|e| e.name == "a");
+                    nodes.extend(link.and_then(|a| self.page_path(a)).map(Node::Page));
+                }
+                "table" if element.has_class("collection-content") => {
+                    nodes.extend(self.rows(element))
+                }
+                // A block's children are written after it, in an indented `div`.
+                "div" if element.has_class("indented") => {
+                    let children = self.blocks(&element.children);
+                    match nodes.last_mut() {
+                        Some(Node::Block {
+                            children: parent, ..
+                        }) => parent.extend(children),
+                        _ => nodes.extend(children),
+                    }
+                }
+                _ if element.is_block() || element.name.is_empty() => {
+                    nodes.extend(self.blocks(&element.children))
+                }
+                _ => nodes.push(self.paragraph(element)),
+            }
+        }
+        nodes
+    }
+
+    /// A paragraph, or a link to a sub-page if that's all it holds.
+    fn paragraph(&self, element: &Element) -> Node {
+        let mut contents = element.children.iter().filter(|child| match child {
+            Content::Text(text) => !text.trim().is_empty(),
+            Content::Element(_) => true,
+        });
+        let only_link = match (contents.next(), contents.next()) {
+            (Some(Content::Element(link)), None) if link.name == "a" => self.page_path(link),
+            _ => None,
+        };
+        if let Some(path) = only_link {
+            return Node::Page(path);
+        }
+
+        Node::Block {
+            id: element.block_id(),
+            block: text_block(self.text(&element.children)),
+            children: self.child_blocks(element),
+        }
+    }
+
+    fn item(&self, list: &Element, item: &Element, id: Option<BlockId>) -> Node {
+        if let Some(details) = item.elements().find(|e| e.name == "details") {
+            return self.toggle(details, id);
+        }
+
+        let properties = text_properties(self.text(&item.children));
+        let block = if list.has_class("to-do-list") {
+            let checked = item
+                .find(&|e| e.has_class("checkbox"))
+                .is_some_and(|checkbox| checkbox.has_class("checkbox-on"));
+            let title = properties.map(|p| p.title).unwrap_or_default();
+            RootBlockType::ToDo {
+                properties: Some(TodoProperties {
+                    title: serde_json::to_value(&title).unwrap_or_default(),
+                    checked: vec![vec![if checked { YesOrNo::Yes } else { YesOrNo::No }]],
+                }),
+            }
+        } else if list.name == "ol" {
+            RootBlockType::NumberedList { properties }
+        } else {
+            RootBlockType::BulletedList { properties }
+        };
+
+        Node::Block {
+            id,
+            block,
+            children: self.child_blocks(item),
+        }
+    }
+
+    /// A toggle, written as `<details><summary>Text</summary>Children</details>`.
+    fn toggle(&self, details: &Element, id: Option<BlockId>) -> Node {
+        let summary = details.elements().find(|e| e.name == "summary");
+        let properties =
+            text_properties(summary.map(|s| self.text(&s.children)).unwrap_or_default());
+        let children = details
+            .children
+            .iter()
+            .filter(|child| !matches!(child, Content::Element(e) if e.name == "summary"))
+            .flat_map(|child| self.blocks(std::slice::from_ref(child)))
+            .collect();
+        Node::Block {
+            id,
+            block: RootBlockType::Toggle { properties },
+            children,
+        }
+    }
+
+    /// Images are stored next to their page, so make their paths relative to the export.
+    fn image_source(&self, source: &str) -> String {
+        match resolve_path(self.dir, source) {
+            Some(path) => path.replace(' ', "%20"),
+            None => source.to_string(),
+        }
+    }
+
+    /// A database's rows. Rows that have a page link to it, and the others are given a page
+    /// listing the row's properties.
+    fn rows(&self, table: &Element) -> Vec<Node> {
+        let columns: Vec<String> = table
+            .find(&|e| e.name == "thead")
+            .and_then(|head| head.find(&|e| e.name == "tr"))
+            .map(|row| {
+                row.elements()
+                    .map(|cell| cell.text().trim().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let body = match table.find(&|e| e.name == "tbody") {
+            Some(body) => body,
+            None => return vec![],
+        };
+
+        let mut rows = vec![];
+        for row in body.elements().filter(|e| e.name == "tr") {
+            let cells: Vec<&Element> = row.elements().filter(|e| e.name == "td").collect();
+            let title = match cells.first() {
+                Some(title) => title,
+                None => continue,
+            };
+            let link = title
+                .find(&|e| e.name == "a")
+                .and_then(|a| self.page_path(a));
+            if let Some(path) = link {
+                rows.push(Node::Page(path));
+                continue;
+            }
+
+            let properties = columns
+                .iter()
+                .zip(&cells)
+                .skip(1)
+                .map(|(column, cell)| (column, cell.text()))
+                .filter(|(_, value)| !value.trim().is_empty())
+                .map(|(column, value)| block(property(column, value.trim()), vec![]))
+                .collect();
+            rows.push(Node::Block {
+                id: row.block_id(),
+                block: page(plain_text(title.text().trim())),
+                children: properties,
+            });
+        }
+        rows
+    }
+
+    /// The properties a database row's page lists under its title.
+    fn properties(&self, table: &Element) -> Vec<Node> {
+        let mut nodes = vec![];
+        for row in table
+            .find(&|e| e.name == "tbody")
+            .unwrap_or(table)
+            .elements()
+        {
+            let name = row.elements().find(|e| e.name == "th").map(Element::text);
+            let value = row.elements().find(|e| e.name == "td").map(Element::text);
+            if let (Some(name), Some(value)) = (name, value) {
+                if !value.trim().is_empty() {
+                    nodes.push(block(property(name.trim(), value.trim()), vec![]));
+                }
+            }
+        }
+        nodes
+    }
+}
+
+/// Parses a page exported as HTML. `dir` is the directory of the page's file in the export,
+/// which is used to resolve links to sub-pages and images.
+///
+/// Unlike Markdown exports, HTML exports keep the IDs of blocks (in each block's `id` attribute),
+/// the colors of text, and a database's rows that don't have a page (in a table on the database's
+/// page), so those are imported too.
+pub fn parse_page(html: &str, dir: &str) -> ParsedPage {
+    let document = parse_html(html);
+    let converter = Converter { dir };
+    let article = document.find(&|e| e.name == "article").unwrap_or(&document);
+    let header = article.elements().find(|e| e.name == "header");
+
+    let title = header
+        .and_then(|header| header.find(&|e| e.has_class("page-title")))
+        .map(|title| converter.text(&title.children));
+    let mut nodes = header
+        .and_then(|header| header.find(&|e| e.name == "table" && e.has_class("properties")))
+        .map(|properties| converter.properties(properties))
+        .unwrap_or_default();
+    nodes.extend(converter.blocks(&article.children));
+
+    ParsedPage { title, nodes }
+}
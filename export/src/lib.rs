@@ -0,0 +1,405 @@
+pub mod database;
+pub mod html;
+pub mod markdown;
+pub mod page;
+
+use anyhow::{anyhow, Result};
+use base::id::BlockId;
+use base::parser::{BaseValueType, BlockTableType, BlockType, Either, RootBlockType};
+use database::parse_database;
+use page::{is_page_file, page, plain_text, property, Node};
+use serde_json::Map;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{Read, Seek};
+use std::path::Path;
+
+/// The pages of a Notion "Markdown & CSV" or "HTML" export, as a block table like the one in a
+/// `loadPageChunk` response. Pass `blocks` to `Renderer::new` and render any of `pages`.
+///
+/// Each page (`.md` or `.html` file) becomes a page block, and its sub-pages are the files in the
+/// folder next to it with the same name. Each database (`.csv` file, or the table on a database's
+/// `.html` page) becomes a page block with a sub-page for each row, using the row's page from the
+/// database's folder if there is one. Pages use the IDs at the end of their file names, and blocks
+/// in HTML exports use the IDs in the HTML. Blocks in Markdown exports don't have IDs, so they are
+/// given made up IDs that are the same every time the export is imported.
+pub struct Export {
+    /// The IDs of the export's top-level pages and databases, sorted by file name.
+    pub pages: Vec<BlockId>,
+    pub blocks: BlockTableType<'static>,
+}
+
+/// Imports an export that has been unzipped into `path`.
+pub fn import_dir<P: AsRef<Path>>(path: P) -> Result<Export> {
+    fn walk(root: &Path, dir: &Path, files: &mut BTreeMap<String, String>) -> Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                walk(root, &path, files)?;
+                continue;
+            }
+
+            let relative = path
+                .strip_prefix(root)?
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            if is_page_file(&relative) {
+                files.insert(relative, fs::read_to_string(&path)?);
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = BTreeMap::new();
+    walk(path.as_ref(), path.as_ref(), &mut files)?;
+    Importer::new(files).import()
+}
+
+/// Imports an export zip, as downloaded from Notion.
+pub fn import_zip<R: Read + Seek>(reader: R) -> Result<Export> {
+    let mut archive = zip::ZipArchive::new(reader)?;
+    let mut files = BTreeMap::new();
+
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        let name = file.name().to_string();
+        if is_page_file(&name) {
+            let mut contents = String::new();
+            file.read_to_string(&mut contents)?;
+            files.insert(name, contents);
+        }
+    }
+
+    Importer::new(files).import()
+}
+
+/// Resolves a link in a page to a path in the export, relative to the export's root. `dir` is the
+/// directory of the page's file. Returns nothing for links to websites.
+pub(crate) fn resolve_path(dir: &str, link: &str) -> Option<String> {
+    if link.contains("://") || link.starts_with('#') || link.starts_with("mailto:") {
+        return None;
+    }
+
+    let link = percent_decode(link.split('#').next()?);
+    let mut segments: Vec<&str> = if link.starts_with('/') {
+        vec![]
+    } else {
+        dir.split('/').filter(|s| !s.is_empty()).collect()
+    };
+    for segment in link.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    Some(segments.join("/"))
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Splits a path into its directory and file name.
+fn split_path(path: &str) -> (&str, &str) {
+    match path.rfind('/') {
+        Some(i) => (&path[..i], &path[i + 1..]),
+        None => ("", path),
+    }
+}
+
+/// The folder that holds a page's sub-pages (or a database's rows): its path without the extension.
+fn page_dir(path: &str) -> &str {
+    path.rfind('.').map(|i| &path[..i]).unwrap_or(path)
+}
+
+/// Splits a page's file name into its title and the ID Notion puts at the end of it.
+fn title_and_id(path: &str) -> (&str, Option<BlockId>) {
+    let (_, name) = split_path(page_dir(path));
+    let mut parts = name.rsplitn(2, ' ');
+    match (parts.next(), parts.next()) {
+        (Some(id), Some(title)) if id.len() == 32 => match BlockId::parse(id) {
+            Ok(id) => (title, Some(id)),
+            Err(_) => (name, None),
+        },
+        _ => (name, None),
+    }
+}
+
+/// Makes up an ID for a block from a key that is unique to it, eg. its page's path and index.
+///
+/// The ID is the 128-bit FNV-1a hash of the key, which (unlike the standard library's hashers) is
+/// fixed, so the IDs stay the same across Rust versions, platforms and runs.
+fn made_up_id(key: &str) -> BlockId {
+    const OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+
+    let hash = key.bytes().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u128::from(byte)).wrapping_mul(PRIME)
+    });
+    BlockId::from_bytes(hash.to_be_bytes())
+}
+
+struct Importer {
+    /// The contents of every page and database file in the export, keyed by path.
+    files: BTreeMap<String, String>,
+    blocks: BlockTableType<'static>,
+    /// The IDs of the pages imported so far, keyed by path, so each page is only imported once.
+    imported: HashMap<String, BlockId>,
+}
+
+impl Importer {
+    fn new(mut files: BTreeMap<String, String>) -> Self {
+        // Newer exports write each database twice, with and without the rows' sub-pages in
+        // `_all.csv`. Only one of them is needed.
+        let duplicates: Vec<String> = files
+            .keys()
+            .filter(|path| {
+                path.strip_suffix("_all.csv")
+                    .is_some_and(|path| files.contains_key(&format!("{}.csv", path)))
+            })
+            .cloned()
+            .collect();
+        for path in duplicates {
+            files.remove(&path);
+        }
+
+        Importer {
+            files,
+            blocks: HashMap::new(),
+            imported: HashMap::new(),
+        }
+    }
+
+    fn import(mut self) -> Result<Export> {
+        let is_sub_page = |path: &str| {
+            let (dir, _) = split_path(path);
+            ["md", "html", "csv"]
+                .iter()
+                .any(|extension| self.files.contains_key(&format!("{}.{}", dir, extension)))
+        };
+        let roots: Vec<String> = self
+            .files
+            .keys()
+            .filter(|p| !is_sub_page(p))
+            .cloned()
+            .collect();
+        if roots.is_empty() {
+            return Err(anyhow!("the export doesn't have any pages"));
+        }
+
+        // Top-level pages don't have a parent in the export, so they share one made up parent.
+        let root_id = made_up_id("");
+        let pages = roots
+            .iter()
+            .map(|path| self.import_page(path, root_id, "space"))
+            .collect::<Result<_>>()?;
+
+        Ok(Export {
+            pages,
+            blocks: self.blocks,
+        })
+    }
+
+    fn insert(
+        &mut self,
+        id: BlockId,
+        parent: BlockId,
+        parent_table: &'static str,
+        block: RootBlockType<'static>,
+        content: Vec<BlockId>,
+    ) {
+        let value = BaseValueType {
+            id,
+            version: 0,
+            created_time: 0,
+            last_edited_time: 0,
+            parent_id: parent,
+            parent_table: Cow::Borrowed(parent_table),
+            alive: true,
+            created_by_table: Cow::Borrowed("notion_user"),
+            created_by_id: Cow::Borrowed(""),
+            last_edited_by_table: Cow::Borrowed("notion_user"),
+            last_edited_by_id: Cow::Borrowed(""),
+            shard_id: None,
+            space_id: None,
+            content: if content.is_empty() {
+                None
+            } else {
+                Some(content)
+            },
+            format: None,
+            block,
+            extra: Map::new(),
+        };
+
+        self.blocks.insert(
+            id,
+            BlockType {
+                role: Cow::Borrowed("reader"),
+                value: Either::Left(value),
+                parse_error: None,
+            },
+        );
+    }
+
+    /// Imports a page or database and its sub-pages, returning its ID.
+    fn import_page(
+        &mut self,
+        path: &str,
+        parent: BlockId,
+        parent_table: &'static str,
+    ) -> Result<BlockId> {
+        if let Some(id) = self.imported.get(path) {
+            return Ok(*id);
+        }
+
+        let (title, id) = title_and_id(path);
+        let id = id.unwrap_or_else(|| made_up_id(path));
+        self.imported.insert(path.to_string(), id);
+
+        let contents = self.files[path].clone();
+        let (title, mut content) = if path.ends_with(".csv") {
+            (plain_text(title), self.import_rows(path, id, &contents)?)
+        } else {
+            let (dir, _) = split_path(path);
+            let page = if path.ends_with(".html") {
+                html::parse_page(&contents, dir)
+            } else {
+                markdown::parse_page(&contents, dir)
+            };
+            let mut index = 0;
+            let content = self.add_nodes(page.nodes, path, &mut index, id, "block")?;
+            (page.title.unwrap_or_else(|| plain_text(title)), content)
+        };
+
+        // Notion links to every sub-page from its parent, but import any that weren't linked too.
+        let prefix = format!("{}/", page_dir(path));
+        let unlinked: Vec<String> = self
+            .files
+            .keys()
+            .filter(|p| p.starts_with(&prefix) && !p[prefix.len()..].contains('/'))
+            .filter(|p| !self.imported.contains_key(*p))
+            .cloned()
+            .collect();
+        for sub_page in unlinked {
+            content.push(self.import_page(&sub_page, id, "block")?);
+        }
+
+        self.insert(id, parent, parent_table, page(title), content);
+        Ok(id)
+    }
+
+    /// Adds the blocks parsed from a page, returning their IDs. `index` counts the page's blocks,
+    /// to make up IDs for the ones that don't have one.
+    fn add_nodes(
+        &mut self,
+        nodes: Vec<Node>,
+        page_path: &str,
+        index: &mut usize,
+        parent: BlockId,
+        parent_table: &'static str,
+    ) -> Result<Vec<BlockId>> {
+        let mut ids = vec![];
+        for node in nodes {
+            match node {
+                Node::Block {
+                    id,
+                    block,
+                    children,
+                } => {
+                    let made_up = made_up_id(&format!("{}#{}", page_path, index));
+                    *index += 1;
+                    let id = id
+                        .filter(|id| !self.blocks.contains_key(id))
+                        .unwrap_or(made_up);
+                    let content = self.add_nodes(children, page_path, index, id, "block")?;
+                    self.insert(id, parent, parent_table, block, content);
+                    ids.push(id);
+                }
+                Node::Page(path) if self.files.contains_key(&path) => {
+                    ids.push(self.import_page(&path, parent, parent_table)?);
+                }
+                // Links to pages that aren't in the export (eg. ones that weren't exported) are dropped.
+                Node::Page(_) => {}
+            }
+        }
+        Ok(ids)
+    }
+
+    /// Adds a page for each row of a database, returning their IDs. Rows that have a page in the
+    /// database's folder use it. The others are given a page listing the row's properties.
+    fn import_rows(&mut self, path: &str, id: BlockId, csv: &str) -> Result<Vec<BlockId>> {
+        let database = parse_database(csv)?;
+        let prefix = format!("{}/", page_dir(path));
+        let row_pages: Vec<String> = self
+            .files
+            .keys()
+            .filter(|p| {
+                p.starts_with(&prefix) && p.ends_with(".md") && !p[prefix.len()..].contains('/')
+            })
+            .cloned()
+            .collect();
+
+        let mut rows = vec![];
+        for (i, (row, title)) in database.rows.iter().zip(database.titles()).enumerate() {
+            let row_page = row_pages
+                .iter()
+                .find(|p| title_and_id(p).0 == title && !self.imported.contains_key(*p));
+            if let Some(row_page) = row_page.cloned() {
+                rows.push(self.import_page(&row_page, id, "block")?);
+                continue;
+            }
+
+            let row_id = made_up_id(&format!("{}#{}", path, i));
+            let properties = database
+                .columns
+                .iter()
+                .zip(row)
+                .skip(1)
+                .filter(|(_, value)| !value.is_empty())
+                .map(|(column, value)| {
+                    let property_id = made_up_id(&format!("{}#{}/{}", path, i, column));
+                    self.insert(
+                        property_id,
+                        row_id,
+                        "block",
+                        property(column, value),
+                        vec![],
+                    );
+                    property_id
+                })
+                .collect();
+
+            self.insert(row_id, id, "block", page(plain_text(title)), properties);
+            rows.push(row_id);
+        }
+        Ok(rows)
+    }
+}
@@ -0,0 +1,351 @@
+use crate::page::{block, image, is_page_file, text_properties, Node, ParsedPage};
+use crate::resolve_path;
+use base::parser::{
+    ContextFormat, FormatType, FormattedText, NoContextFormat, RootBlockType, TodoProperties,
+    YesOrNo,
+};
+use pulldown_cmark::{Event, Options, Parser, Tag};
+use std::borrow::Cow;
+
+enum Kind {
+    Root,
+    Paragraph,
+    Heading(u32),
+    Quote,
+    Item {
+        ordered: bool,
+        checked: Option<bool>,
+    },
+    Toggle,
+    Code,
+}
+
+/// A block that is still being parsed.
+struct Container {
+    kind: Kind,
+    text: Vec<FormattedText<'static>>,
+    children: Vec<Node>,
+    /// Paths of the pages in the export linked from the block's text.
+    page_links: Vec<String>,
+    /// Whether the block has any text outside of links.
+    has_plain_text: bool,
+    /// Whether a toggle's `</details>` has been seen. It's closed once the blocks in it that
+    /// pulldown-cmark hasn't ended yet (eg. the paragraph the tag was in) have been.
+    closed: bool,
+}
+
+impl Container {
+    fn new(kind: Kind) -> Self {
+        Container {
+            kind,
+            text: vec![],
+            children: vec![],
+            page_links: vec![],
+            has_plain_text: false,
+            closed: false,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.text.is_empty() && self.children.is_empty()
+    }
+}
+
+struct PageParser<'d> {
+    /// The directory of the page's file, which links are relative to.
+    dir: &'d str,
+    stack: Vec<Container>,
+    /// Whether each list the parser is in is ordered, innermost last.
+    lists: Vec<bool>,
+    formats: Vec<FormatType<'static>>,
+    link_depth: usize,
+    /// The source and alt text of the image being parsed.
+    image: Option<(String, String)>,
+    title: Option<Vec<FormattedText<'static>>>,
+    /// Whether the parser is inside a toggle's `<summary>`, which holds the toggle's text.
+    in_summary: bool,
+}
+
+impl<'d> PageParser<'d> {
+    fn top(&mut self) -> &mut Container {
+        self.stack.last_mut().unwrap()
+    }
+
+    fn push_text(&mut self, text: &str, extra: Option<NoContextFormat>) {
+        if let Some((_, alt)) = &mut self.image {
+            alt.push_str(text);
+            return;
+        }
+
+        let mut formatting = self.formats.clone();
+        if let Some(format) = extra {
+            formatting.push(FormatType::NoContext(format));
+        }
+        if let Kind::Code = self.top().kind {
+            formatting.push(FormatType::NoContext(NoContextFormat::Code));
+        }
+
+        let plain = self.link_depth == 0 && !text.trim().is_empty();
+        let top = self.top();
+        top.has_plain_text |= plain;
+        top.text.push(FormattedText {
+            text: Cow::Owned(text.to_string()),
+            formatting: if formatting.is_empty() {
+                None
+            } else {
+                Some(formatting)
+            },
+        });
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.stack.push(Container::new(Kind::Paragraph)),
+            Tag::Heading(level) => self.stack.push(Container::new(Kind::Heading(level))),
+            Tag::BlockQuote => self.stack.push(Container::new(Kind::Quote)),
+            Tag::CodeBlock(_) => self.stack.push(Container::new(Kind::Code)),
+            Tag::List(start) => self.lists.push(start.is_some()),
+            Tag::Item => {
+                let ordered = self.lists.last().copied().unwrap_or(false);
+                self.stack.push(Container::new(Kind::Item {
+                    ordered,
+                    checked: None,
+                }));
+            }
+            Tag::Emphasis => self
+                .formats
+                .push(FormatType::NoContext(NoContextFormat::Italic)),
+            Tag::Strong => self
+                .formats
+                .push(FormatType::NoContext(NoContextFormat::Bold)),
+            Tag::Strikethrough => self
+                .formats
+                .push(FormatType::NoContext(NoContextFormat::Strike)),
+            Tag::Link(_, dest, _) => {
+                if let Some(path) = resolve_path(self.dir, &dest).filter(|p| is_page_file(p)) {
+                    self.top().page_links.push(path);
+                }
+                self.link_depth += 1;
+                self.formats
+                    .push(FormatType::Context(ContextFormat::Link(Cow::Owned(
+                        dest.to_string(),
+                    ))));
+            }
+            Tag::Image(_, dest, _) => {
+                // Images are stored next to their page, so make their paths relative to the export.
+                let source = match resolve_path(self.dir, &dest) {
+                    Some(path) => path.replace(' ', "%20"),
+                    None => dest.to_string(),
+                };
+                self.image = Some((source, String::new()));
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: Tag) {
+        self.end_tag(tag);
+        while self.stack.len() > 1 && self.top().closed {
+            self.close();
+        }
+    }
+
+    fn end_tag(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.end_paragraph(),
+            Tag::Heading(_) | Tag::BlockQuote | Tag::CodeBlock(_) | Tag::Item => self.close(),
+            Tag::List(_) => {
+                self.lists.pop();
+            }
+            Tag::Emphasis | Tag::Strong | Tag::Strikethrough => {
+                self.formats.pop();
+            }
+            Tag::Link(..) => {
+                self.link_depth -= 1;
+                self.formats.pop();
+            }
+            Tag::Image(..) => {
+                if let Some((source, alt)) = self.image.take() {
+                    self.top().children.push(block(image(source, alt), vec![]));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn end_paragraph(&mut self) {
+        if self.stack.len() == 1 {
+            return;
+        }
+        let paragraph = self.stack.pop().unwrap();
+        let parent = self.top();
+
+        if paragraph.page_links.len() == 1 && !paragraph.has_plain_text {
+            let link = paragraph.page_links.into_iter().next().unwrap();
+            parent.children.push(Node::Page(link));
+            parent.children.extend(paragraph.children);
+        } else if paragraph.text.is_empty() {
+            parent.children.extend(paragraph.children);
+        } else if matches!(parent.kind, Kind::Item { .. } | Kind::Quote | Kind::Toggle)
+            && parent.is_empty()
+        {
+            // The first paragraph of a list item or quote is the block's own text.
+            parent.text = paragraph.text;
+            parent.children.extend(paragraph.children);
+        } else {
+            let text = RootBlockType::Text {
+                properties: text_properties(paragraph.text),
+            };
+            parent.children.push(block(text, vec![]));
+            parent.children.extend(paragraph.children);
+        }
+    }
+
+    /// Pops the innermost block and adds it to its parent. The root is never popped.
+    fn close(&mut self) {
+        if self.stack.len() == 1 {
+            return;
+        }
+        let container = self.stack.pop().unwrap();
+        let is_first = self.stack.len() == 1 && self.top().is_empty();
+        let properties = text_properties(container.text);
+
+        let node = match container.kind {
+            Kind::Heading(1) if is_first && self.title.is_none() => {
+                self.title = Some(properties.map(|p| p.title).unwrap_or_default());
+                return;
+            }
+            Kind::Heading(1) => RootBlockType::Header { properties },
+            Kind::Heading(2) => RootBlockType::SubHeader { properties },
+            Kind::Heading(_) => RootBlockType::SubSubHeader { properties },
+            Kind::Quote => RootBlockType::Quote { properties },
+            Kind::Toggle => RootBlockType::Toggle { properties },
+            Kind::Item {
+                ordered: _,
+                checked: Some(checked),
+            } => {
+                let title = properties.map(|p| p.title).unwrap_or_default();
+                RootBlockType::ToDo {
+                    properties: Some(TodoProperties {
+                        title: serde_json::to_value(&title).unwrap_or_default(),
+                        checked: vec![vec![if checked { YesOrNo::Yes } else { YesOrNo::No }]],
+                    }),
+                }
+            }
+            Kind::Item { ordered: true, .. } => RootBlockType::NumberedList { properties },
+            Kind::Item { ordered: false, .. } => RootBlockType::BulletedList { properties },
+            Kind::Code => {
+                let mut properties = properties;
+                if let Some(last) = properties.as_mut().and_then(|p| p.title.last_mut()) {
+                    let trimmed = last.text.trim_end_matches('\n').len();
+                    last.text.to_mut().truncate(trimmed);
+                }
+                RootBlockType::Text { properties }
+            }
+            Kind::Paragraph | Kind::Root => RootBlockType::Text { properties },
+        };
+        self.top().children.push(block(node, container.children));
+    }
+
+    /// Handles the `<details>` and `<summary>` tags Notion writes toggles as. Other HTML is skipped.
+    fn html(&mut self, html: &str) {
+        let mut rest = html;
+        while let Some(start) = rest.find('<') {
+            let text = rest[..start].trim();
+            if self.in_summary && !text.is_empty() {
+                self.push_text(text, None);
+            }
+
+            let end = match rest[start..].find('>') {
+                Some(end) => start + end,
+                None => break,
+            };
+            let tag = rest[start + 1..end].trim().to_ascii_lowercase();
+            rest = &rest[end + 1..];
+
+            if tag == "details" || tag.starts_with("details ") {
+                self.stack.push(Container::new(Kind::Toggle));
+            } else if tag == "summary" {
+                self.in_summary = matches!(self.top().kind, Kind::Toggle);
+            } else if tag == "/summary" {
+                self.in_summary = false;
+            } else if tag == "/details" {
+                self.in_summary = false;
+                let toggle = self
+                    .stack
+                    .iter_mut()
+                    .rev()
+                    .find(|c| matches!(c.kind, Kind::Toggle) && !c.closed);
+                if let Some(toggle) = toggle {
+                    toggle.closed = true;
+                }
+                if self.top().closed {
+                    self.close();
+                }
+            }
+        }
+
+        let text = rest.trim();
+        if self.in_summary && !text.is_empty() && !text.contains('<') {
+            self.push_text(text, None);
+        }
+    }
+
+    fn finish(mut self) -> ParsedPage {
+        while self.stack.len() > 1 {
+            self.close();
+        }
+
+        ParsedPage {
+            title: self.title,
+            nodes: self.stack.pop().unwrap().children,
+        }
+    }
+}
+
+/// Parses a page exported as Markdown. `dir` is the directory of the page's file in the export,
+/// which is used to resolve links to sub-pages and images.
+pub fn parse_page(markdown: &str, dir: &str) -> ParsedPage {
+    let options = Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut parser = PageParser {
+        dir,
+        stack: vec![Container::new(Kind::Root)],
+        lists: vec![],
+        formats: vec![],
+        link_depth: 0,
+        image: None,
+        title: None,
+        in_summary: false,
+    };
+
+    for event in Parser::new_ext(markdown, options) {
+        match event {
+            Event::Start(tag) => parser.start(tag),
+            Event::End(tag) => parser.end(tag),
+            Event::Text(text) => parser.push_text(&text, None),
+            Event::Code(code) => parser.push_text(&code, Some(NoContextFormat::Code)),
+            Event::Html(html) => parser.html(&html),
+            Event::SoftBreak | Event::HardBreak => parser.push_text("\n", None),
+            Event::Rule => parser
+                .top()
+                .children
+                .push(block(RootBlockType::Divider, vec![])),
+            Event::TaskListMarker(checked) => {
+                let item = parser
+                    .stack
+                    .iter_mut()
+                    .rev()
+                    .find_map(|c| match &mut c.kind {
+                        Kind::Item { checked, .. } => Some(checked),
+                        _ => None,
+                    });
+                if let Some(item_checked) = item {
+                    *item_checked = Some(checked);
+                }
+            }
+            Event::FootnoteReference(_) => {}
+        }
+    }
+
+    parser.finish()
+}
@@ -0,0 +1,115 @@
+use base::id::BlockId;
+use base::parser::{
+    FormatType, FormattedText, ImageFormat, ImageProperties, NoContextFormat, PageProperties,
+    RootBlockType, TextProperties,
+};
+use serde_json::Map;
+use std::borrow::Cow;
+
+/// A block parsed from a page's file, before it has been added to the block table.
+pub enum Node {
+    Block {
+        /// The block's ID, if the export has it. HTML exports do; Markdown exports don't, so the
+        /// importer makes one up.
+        id: Option<BlockId>,
+        block: RootBlockType<'static>,
+        children: Vec<Node>,
+    },
+    /// A link to another page or database in the export, by its path. Notion writes sub-pages
+    /// as a paragraph holding only a link to the sub-page's file.
+    Page(String),
+}
+
+/// A page's file, parsed into blocks.
+pub struct ParsedPage {
+    /// The title Notion starts every page with.
+    pub title: Option<Vec<FormattedText<'static>>>,
+    pub nodes: Vec<Node>,
+}
+
+/// Whether a path in the export is a page (`.md` or `.html`) or database (`.csv`) file.
+pub fn is_page_file(path: &str) -> bool {
+    path.ends_with(".md") || path.ends_with(".html") || path.ends_with(".csv")
+}
+
+pub(crate) fn block(block: RootBlockType<'static>, children: Vec<Node>) -> Node {
+    Node::Block {
+        id: None,
+        block,
+        children,
+    }
+}
+
+pub(crate) fn plain_text(text: &str) -> Vec<FormattedText<'static>> {
+    vec![FormattedText {
+        text: Cow::Owned(text.to_string()),
+        formatting: None,
+    }]
+}
+
+pub(crate) fn text_properties(
+    title: Vec<FormattedText<'static>>,
+) -> Option<TextProperties<'static>> {
+    if title.is_empty() {
+        None
+    } else {
+        Some(TextProperties {
+            title,
+            extra: Map::new(),
+        })
+    }
+}
+
+pub(crate) fn page(title: Vec<FormattedText<'static>>) -> RootBlockType<'static> {
+    RootBlockType::Page {
+        format: None,
+        file_ids: None,
+        permissions: None,
+        properties: PageProperties { title },
+    }
+}
+
+/// A line showing one of a database row's properties, eg. "**Tags:** a, b".
+pub(crate) fn property(column: &str, value: &str) -> RootBlockType<'static> {
+    RootBlockType::Text {
+        properties: Some(TextProperties {
+            title: vec![
+                FormattedText {
+                    text: Cow::Owned(format!("{}: ", column)),
+                    formatting: Some(vec![FormatType::NoContext(NoContextFormat::Bold)]),
+                },
+                FormattedText {
+                    text: Cow::Owned(value.to_string()),
+                    formatting: None,
+                },
+            ],
+            extra: Map::new(),
+        }),
+    }
+}
+
+pub(crate) fn image(source: String, caption: String) -> RootBlockType<'static> {
+    let caption = if caption.is_empty() {
+        None
+    } else {
+        Some(vec![vec![caption]])
+    };
+
+    // Exports don't record an image's size, so the format is left blank.
+    RootBlockType::Image {
+        properties: ImageProperties {
+            source: vec![vec![source]],
+            caption,
+        },
+        format: ImageFormat {
+            block_width: 0,
+            block_height: 0,
+            display_source: None,
+            block_full_width: None,
+            block_page_width: None,
+            block_aspect_ratio: 0.0,
+            block_preserve_scale: false,
+        },
+        file_ids: vec![],
+    }
+}
@@ -0,0 +1,198 @@
+use base::id::BlockId;
+use base::parser::Either;
+use export::{import_dir, Export};
+use serde_json::Value;
+
+/// The plain text of a block's `title` (or `source`, for images).
+fn text(block: &Value) -> String {
+    let properties = &block["properties"];
+    let runs = properties["title"]
+        .as_array()
+        .or_else(|| properties["source"].as_array());
+    runs.into_iter()
+        .flatten()
+        .filter_map(|run| run[0].as_str())
+        .collect()
+}
+
+/// A line for each block under `id`: its type and text, indented by its depth.
+fn outline(export: &Export, id: &BlockId, depth: usize, lines: &mut Vec<String>) {
+    let value = match &export.blocks[id].value {
+        Either::Left(value) => value,
+        Either::Right(raw) => panic!("{} isn't a block: {}", id, raw),
+    };
+    let block = serde_json::to_value(&value.block).unwrap();
+    let mut line = format!(
+        "{}{} {}",
+        "  ".repeat(depth),
+        block["type"].as_str().unwrap(),
+        text(&block)
+    );
+    if let Some(checked) = block["properties"]["checked"][0][0].as_str() {
+        line.push_str(&format!(" [{}]", checked));
+    }
+    if let Some(caption) = block["properties"]["caption"][0][0].as_str() {
+        line.push_str(&format!(" ({})", caption));
+    }
+    lines.push(line.trim_end().to_string());
+
+    // Don't repeat sub-pages that are listed elsewhere.
+    for child in value.content.iter().flatten() {
+        if let Either::Left(child_value) = &export.blocks[child].value {
+            if child_value.parent_id == *id {
+                outline(export, child, depth + 1, lines);
+            }
+        }
+    }
+}
+
+fn import(path: &str) -> (Export, Vec<String>) {
+    let export = import_dir(path).unwrap();
+    let mut lines = vec![];
+    for page in &export.pages {
+        outline(&export, page, 0, &mut lines);
+    }
+    (export, lines)
+}
+
+fn id(id: &str) -> BlockId {
+    BlockId::parse(id).unwrap()
+}
+
+#[test]
+fn imports_html_exports() {
+    let (export, lines) = import("fixtures/html");
+    assert_eq!(
+        lines,
+        [
+            "page Home & Garden",
+            "  text Plain, bold, italic, struck, code, red and a link.",
+            "  sub_header Lists",
+            "  bulleted_list Fruit",
+            "    bulleted_list Apple",
+            "  numbered_list First",
+            "  to_do Done [Yes]",
+            "  to_do Not done [No]",
+            "  toggle More",
+            "    text Hidden",
+            "  quote Quoted",
+            "  divider",
+            "  image Home%200f1e2d3c4b5a69788796a5b4c3d2e1f0/garden.png (The garden)",
+            "  text let x = 1 < 2;",
+            "  text Parent",
+            "    text Child",
+            "  page Sub Page",
+            "    text Below home.",
+            "  page Tasks",
+            "    page Write docs",
+            "      text Status: Doing",
+            "      text Start with the README.",
+            "    page Ship it",
+            "      text Status: Todo",
+        ]
+    );
+
+    // Pages and blocks keep the IDs they have in Notion.
+    assert_eq!(export.pages, [id("0f1e2d3c4b5a69788796a5b4c3d2e1f0")]);
+    for block in &[
+        "a1b2c3d4-0001-4000-8000-000000000004",
+        "a1b2c3d4-0001-4000-8000-000000000009",
+        "5d4c3b2a19084f7e8d6c5b4a39281706",
+        "7e6d5c4b3a294817a6f5e4d3c2b1a098",
+        "9a8b7c6d-5e4f-4a3b-8c2d-1e0f9a8b7c6d",
+    ] {
+        assert!(
+            export.blocks.contains_key(&id(block)),
+            "{} is missing",
+            block
+        );
+    }
+}
+
+#[test]
+fn keeps_html_formatting() {
+    let (export, _) = import("fixtures/html");
+    let paragraph = match &export.blocks[&id("a1b2c3d4-0001-4000-8000-000000000001")].value {
+        Either::Left(value) => serde_json::to_value(&value.block).unwrap(),
+        Either::Right(_) => unreachable!(),
+    };
+    assert_eq!(
+        paragraph["properties"]["title"],
+        serde_json::json!([
+            ["Plain, "],
+            ["bold", [["b"]]],
+            [", "],
+            ["italic", [["i"]]],
+            [", "],
+            ["struck", [["s"]]],
+            [", "],
+            ["code", [["c"]]],
+            [", "],
+            ["red", [["h", "red"]]],
+            [" and "],
+            ["a link", [["a", "https://example.com/?a=1&b=2"]]],
+            ["."]
+        ])
+    );
+}
+
+#[test]
+fn imports_markdown_exports() {
+    let (export, lines) = import("fixtures/markdown");
+    assert_eq!(
+        lines,
+        [
+            "page Home",
+            "  text Some bold text and a link.",
+            "  sub_header Lists",
+            "  bulleted_list Fruit",
+            "    bulleted_list Apple",
+            "    bulleted_list Pear",
+            "  numbered_list First",
+            "  numbered_list Second",
+            "  to_do Done [Yes]",
+            "  to_do Not done [No]",
+            "  toggle More",
+            "    text Hidden",
+            "  quote Quoted",
+            "  divider",
+            "  image Home%203c2b1a0f9e8d4c7b8a6f5e4d3c2b1a09/garden.png (The garden)",
+            "  page Sub Page",
+            "    sub_sub_header Below home",
+            "    text Text under the heading.",
+            // The database is only imported once, though it's in the export twice.
+            "  page Tasks",
+            // A row with a page, then one without.
+            "    page Write docs",
+            "      text Status: Doing",
+            "      text Start with the README.",
+            "    page Ship it",
+            "      text Status: Todo",
+            "      text Notes: Soon, really",
+            "page Notes",
+            "  text A page without an ID in its name.",
+        ]
+    );
+
+    // Pages use the IDs in their file names, and the others are the same on every import.
+    assert_eq!(
+        export.pages,
+        [
+            id("3c2b1a0f9e8d4c7b8a6f5e4d3c2b1a09"),
+            id("d304107544659a7644c5e20d193e140d"),
+        ]
+    );
+    for page in &[
+        "8f7e6d5c4b3a49281706f5e4d3c2b1a0",
+        "6a5b4c3d2e1f4a0b9c8d7e6f5a4b3c2d",
+        "1b2c3d4e5f6a4b7c8d9e0f1a2b3c4d5e",
+    ] {
+        assert!(export.blocks.contains_key(&id(page)), "{} is missing", page);
+    }
+    let again = import_dir("fixtures/markdown").unwrap();
+    let mut ids: Vec<_> = export.blocks.keys().collect();
+    let mut ids_again: Vec<_> = again.blocks.keys().collect();
+    ids.sort_by_key(|id| id.to_string());
+    ids_again.sort_by_key(|id| id.to_string());
+    assert_eq!(ids, ids_again);
+}
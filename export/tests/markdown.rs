@@ -0,0 +1,54 @@
+use base::parser::RootBlockType;
+use export::markdown::parse_page;
+use export::page::Node;
+
+/// The type and plain text of each block, with its children indented under it.
+fn outline(nodes: &[Node], depth: usize, lines: &mut Vec<String>) {
+    for node in nodes {
+        match node {
+            Node::Block {
+                block, children, ..
+            } => {
+                let (name, properties) = match block {
+                    RootBlockType::Text { properties } => ("text", properties),
+                    RootBlockType::Toggle { properties } => ("toggle", properties),
+                    _ => ("other", &None),
+                };
+                let text: String = properties
+                    .iter()
+                    .flat_map(|p| &p.title)
+                    .map(|t| t.text.as_ref())
+                    .collect();
+                lines.push(format!("{}{} {}", "  ".repeat(depth), name, text));
+                outline(children, depth + 1, lines);
+            }
+            Node::Page(path) => lines.push(format!("{}page {}", "  ".repeat(depth), path)),
+        }
+    }
+}
+
+fn parse(markdown: &str) -> Vec<String> {
+    let mut lines = vec![];
+    outline(&parse_page(markdown, "").nodes, 0, &mut lines);
+    lines
+}
+
+#[test]
+fn parses_toggles() {
+    let markdown = "# T\n\n<details>\n<summary>S</summary>\n\nfoo\n\n</details>\n\nafter\n";
+    assert_eq!(parse(markdown), ["toggle S", "  text foo", "text after"]);
+}
+
+#[test]
+fn closes_toggles_after_the_paragraph_they_end_in() {
+    let markdown = "# T\n\n<details>\n<summary>S\n\nfoo </details>\n\nafter\n";
+    assert_eq!(parse(markdown), ["toggle S", "  text foo ", "text after"]);
+}
+
+#[test]
+fn ignores_closing_tags_without_a_toggle() {
+    assert_eq!(
+        parse("a </details>\n\n</details>\n\nb\n"),
+        ["text a ", "text b"]
+    );
+}
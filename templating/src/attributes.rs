@@ -1,15 +1,35 @@
-pub struct Attribute(String);
+use crate::tags::escape;
+use std::borrow::Cow;
+use std::fmt;
+
+pub struct Attribute {
+    text: String,
+    /// Whether it's a boolean attribute, in which case `text` is just its name.
+    boolean: bool,
+}
 
 impl Attribute {
-    pub fn to_string(&self) -> String {
-        match self {
-            Attribute(str) => str.clone(),
-        }
+    pub(crate) fn into_string(self) -> String {
+        self.text
+    }
+
+    pub(crate) fn is_boolean(&self) -> bool {
+        self.boolean
+    }
+}
+
+impl fmt::Display for Attribute {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.text)
     }
 }
 
+/// An attribute with a value. The value is escaped, so it can't end the attribute early.
 pub fn attribute(key: &str, value: &str) -> Attribute {
-    Attribute([key, "=\"", value, "\""].concat())
+    Attribute {
+        text: [key, "=\"", &escape(value), "\""].concat(),
+        boolean: false,
+    }
 }
 
 pub fn style_list(input: Vec<(&str, &str)>) -> Attribute {
@@ -38,6 +58,10 @@ pub fn r#type(name: &str) -> Attribute {
     attribute("type", name)
 }
 
+pub fn start(name: &str) -> Attribute {
+    attribute("start", name)
+}
+
 pub fn value(name: &str) -> Attribute {
     attribute("value", name)
 }
@@ -50,6 +74,113 @@ pub fn selected(name: &str) -> Attribute {
     attribute("selected", name)
 }
 
-pub fn href(name: &str) -> Attribute {
-    attribute("href", name)
+/// A link's URL, checked with the default `UrlSanitizer`.
+pub fn href(url: &str) -> Attribute {
+    href_with(url, &UrlSanitizer::default())
+}
+
+pub fn href_with(url: &str, sanitizer: &UrlSanitizer) -> Attribute {
+    attribute("href", sanitizer.sanitize(url))
+}
+
+/// An embedded resource's URL, checked with the default `UrlSanitizer`.
+pub fn src(url: &str) -> Attribute {
+    src_with(url, &UrlSanitizer::default())
+}
+
+pub fn src_with(url: &str, sanitizer: &UrlSanitizer) -> Attribute {
+    attribute("src", sanitizer.sanitize(url))
+}
+
+/// The URL of a quote's source, checked with the default `UrlSanitizer`.
+pub fn cite(url: &str) -> Attribute {
+    cite_with(url, &UrlSanitizer::default())
+}
+
+pub fn cite_with(url: &str, sanitizer: &UrlSanitizer) -> Attribute {
+    attribute("cite", sanitizer.sanitize(url))
+}
+
+/// A boolean attribute, like `open` or `checked`, which is true when it's there and has no value.
+pub fn boolean(name: &str) -> Attribute {
+    Attribute {
+        text: name.to_string(),
+        boolean: true,
+    }
+}
+
+/// What a URL that isn't allowed is replaced with.
+pub const BLOCKED_URL: &str = "#";
+
+/// Decides which URLs can be put in `href` and `src` attributes by their scheme, so links like
+/// `javascript:alert(1)` can't run anything. Relative URLs (which have no scheme) are always
+/// allowed. By default, `http`, `https`, `mailto` and `tel` are allowed.
+#[derive(Clone, Debug)]
+pub struct UrlSanitizer {
+    schemes: Vec<Cow<'static, str>>,
+}
+
+impl Default for UrlSanitizer {
+    fn default() -> Self {
+        UrlSanitizer::with_schemes(vec!["http", "https", "mailto", "tel"])
+    }
+}
+
+impl UrlSanitizer {
+    /// Creates a sanitizer that only allows the given schemes (and relative URLs).
+    pub fn with_schemes<I, S>(schemes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<Cow<'static, str>>,
+    {
+        UrlSanitizer {
+            schemes: schemes.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Allows another scheme, like `ftp` or `notion`.
+    pub fn allow<S: Into<Cow<'static, str>>>(mut self, scheme: S) -> Self {
+        self.schemes.push(scheme.into());
+        self
+    }
+
+    pub fn is_allowed(&self, url: &str) -> bool {
+        match scheme(url) {
+            Some(scheme) => self
+                .schemes
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(&scheme)),
+            None => true,
+        }
+    }
+
+    /// Returns the URL if it's allowed, and `BLOCKED_URL` if it isn't.
+    pub fn sanitize<'u>(&self, url: &'u str) -> &'u str {
+        if self.is_allowed(url) {
+            url
+        } else {
+            BLOCKED_URL
+        }
+    }
+}
+
+/// The scheme of a URL, if it has one. Like browsers, leading spaces and control characters are
+/// skipped, and tabs and newlines are ignored wherever they are, so `" java\tscript:"` has the
+/// scheme `javascript`.
+fn scheme(url: &str) -> Option<String> {
+    let mut scheme = String::new();
+    let chars = url
+        .trim_start_matches(|c: char| c <= ' ')
+        .chars()
+        .filter(|c| !matches!(c, '\t' | '\n' | '\r'));
+
+    for c in chars {
+        match c {
+            ':' if !scheme.is_empty() => return Some(scheme),
+            'a'..='z' | 'A'..='Z' => scheme.push(c),
+            '0'..='9' | '+' | '-' | '.' if !scheme.is_empty() => scheme.push(c),
+            _ => return None,
+        }
+    }
+    None
 }
@@ -0,0 +1,511 @@
+use crate::attributes::{self, attribute, boolean, href, src, style_list, Attribute};
+use crate::tags::{element, text, Tag};
+use std::marker::PhantomData;
+
+/// The name of an element, and whether it's a void element. Implemented by the types in `elements`.
+pub trait ElementType {
+    const NAME: &'static str;
+    /// Void elements (like `hr` and `img`) can't have children, and have no closing tag.
+    const VOID: bool;
+}
+
+/// Elements that can have children, which is every element that isn't void.
+pub trait Container: ElementType {}
+
+/// An HTML element that is being built, like `div().class("x").child(...)`.
+///
+/// The element's type decides which methods it has: only elements that can have children have
+/// `child`, and attributes that only mean something on some elements (like `href` or `start`) are
+/// only on those elements, so mistakes like putting children in an `hr` don't compile. Attributes
+/// that are allowed on every element (like `class` and `id`) are on all of them. Attribute values
+/// are escaped, and URLs in `href`, `src` and `cite` are checked with the default `UrlSanitizer`.
+///
+/// Turn it into a `Tag` with `build` (or `into`):
+///
+/// ```
+/// use templating::html;
+///
+/// let list = html::ul().class("list").child(html::li().text("a < b"));
+/// assert_eq!(list.build().to_string(), r#"<ul class="list"><li>a &lt; b</li></ul>"#);
+/// ```
+///
+/// Void elements can't have children:
+///
+/// ```compile_fail,E0599
+/// use templating::html;
+///
+/// html::hr().child(html::span());
+/// ```
+///
+/// And attributes can only be put on the elements they mean something on:
+///
+/// ```compile_fail,E0599
+/// use templating::html;
+///
+/// html::div().href("/page");
+/// ```
+pub struct Element<E> {
+    attributes: Vec<Attribute>,
+    children: Vec<Tag>,
+    element: PhantomData<E>,
+}
+
+impl<E: ElementType> Element<E> {
+    fn new() -> Self {
+        Element {
+            attributes: vec![],
+            children: vec![],
+            element: PhantomData,
+        }
+    }
+
+    fn attribute(self, name: &str, value: &str) -> Self {
+        self.push(attribute(name, value))
+    }
+
+    fn push(mut self, attribute: Attribute) -> Self {
+        self.attributes.push(attribute);
+        self
+    }
+
+    /// Adds a boolean attribute if `on` is true.
+    fn flag(mut self, name: &str, on: bool) -> Self {
+        if on {
+            self.attributes.push(boolean(name));
+        }
+        self
+    }
+
+    pub fn class(self, name: &str) -> Self {
+        self.attribute("class", name)
+    }
+
+    pub fn id(self, id: &str) -> Self {
+        self.attribute("id", id)
+    }
+
+    pub fn title(self, title: &str) -> Self {
+        self.attribute("title", title)
+    }
+
+    pub fn lang(self, lang: &str) -> Self {
+        self.attribute("lang", lang)
+    }
+
+    /// Sets the `style` attribute from a list of properties and values.
+    pub fn style(self, declarations: Vec<(&str, &str)>) -> Self {
+        self.push(style_list(declarations))
+    }
+
+    pub fn hidden(self, hidden: bool) -> Self {
+        self.flag("hidden", hidden)
+    }
+
+    /// Adds a `data-*` attribute. `name` is the part after `data-`.
+    pub fn data(self, name: &str, value: &str) -> Self {
+        self.attribute(&["data-", name].concat(), value)
+    }
+
+    pub fn build(self) -> Tag {
+        element(E::NAME.into(), self.attributes, self.children, E::VOID)
+    }
+}
+
+impl<E: Container> Element<E> {
+    pub fn child<T: Into<Tag>>(mut self, child: T) -> Self {
+        self.children.push(child.into());
+        self
+    }
+
+    pub fn children<I>(mut self, children: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Tag>,
+    {
+        self.children.extend(children.into_iter().map(Into::into));
+        self
+    }
+
+    /// Adds a piece of text as a child, escaping it.
+    pub fn text(self, t: &str) -> Self {
+        self.child(text(t))
+    }
+}
+
+impl<E: ElementType> From<Element<E>> for Tag {
+    fn from(element: Element<E>) -> Tag {
+        element.build()
+    }
+}
+
+macro_rules! elements {
+    ($($name:ident: $marker:ident $($void:ident)?,)*) => {
+        /// The types of the elements, used as `Element`'s type parameter.
+        pub mod elements {
+            $(
+                #[doc = concat!("The `", stringify!($name), "` element.")]
+                pub struct $marker;
+            )*
+        }
+
+        $(
+            impl ElementType for elements::$marker {
+                const NAME: &'static str = stringify!($name);
+                const VOID: bool = elements!(@void $($void)?);
+            }
+
+            elements!(@container $marker $($void)?);
+
+            #[doc = concat!("Starts building a `", stringify!($name), "` element.")]
+            pub fn $name() -> Element<elements::$marker> {
+                Element::new()
+            }
+        )*
+    };
+    (@void) => { false };
+    (@void void) => { true };
+    (@container $marker:ident) => {
+        impl Container for elements::$marker {}
+    };
+    (@container $marker:ident void) => {};
+}
+
+elements! {
+    a: A,
+    abbr: Abbr,
+    address: Address,
+    article: Article,
+    aside: Aside,
+    audio: Audio,
+    b: B,
+    bdo: Bdo,
+    blockquote: Blockquote,
+    br: Br void,
+    button: Button,
+    canvas: Canvas,
+    caption: Caption,
+    cite: Cite,
+    code: Code,
+    col: Col void,
+    colgroup: Colgroup,
+    datalist: Datalist,
+    dd: Dd,
+    del: Del,
+    details: Details,
+    dfn: Dfn,
+    div: Div,
+    dl: Dl,
+    dt: Dt,
+    em: Em,
+    embed: Embed void,
+    fieldset: Fieldset,
+    figcaption: Figcaption,
+    figure: Figure,
+    footer: Footer,
+    form: Form,
+    h1: H1,
+    h2: H2,
+    h3: H3,
+    h4: H4,
+    h5: H5,
+    h6: H6,
+    header: Header,
+    hr: Hr void,
+    i: I,
+    iframe: Iframe,
+    img: Img void,
+    input: Input void,
+    ins: Ins,
+    kbd: Kbd,
+    label: Label,
+    legend: Legend,
+    li: Li,
+    main: Main,
+    mark: Mark,
+    menu: Menu,
+    meter: Meter,
+    nav: Nav,
+    object: Object,
+    ol: Ol,
+    optgroup: Optgroup,
+    option: Option,
+    output: Output,
+    p: P,
+    param: Param void,
+    pre: Pre,
+    progress: Progress,
+    q: Q,
+    rp: Rp,
+    rt: Rt,
+    ruby: Ruby,
+    s: S,
+    samp: Samp,
+    section: Section,
+    select: Select,
+    small: Small,
+    source: Source void,
+    span: Span,
+    strike: Strike,
+    strong: Strong,
+    sub: Sub,
+    summary: Summary,
+    sup: Sup,
+    table: Table,
+    tbody: Tbody,
+    td: Td,
+    textarea: Textarea,
+    tfoot: Tfoot,
+    th: Th,
+    thead: Thead,
+    time: Time,
+    tr: Tr,
+    track: Track void,
+    u: U,
+    ul: Ul,
+    var: Var,
+    video: Video,
+    wbr: Wbr void,
+}
+
+/// The kinds of numbers an `ol` can use, for its `type` attribute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Numbering {
+    /// 1, 2, 3
+    Decimal,
+    /// a, b, c
+    LowerAlpha,
+    /// A, B, C
+    UpperAlpha,
+    /// i, ii, iii
+    LowerRoman,
+    /// I, II, III
+    UpperRoman,
+}
+
+impl Numbering {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Numbering::Decimal => "1",
+            Numbering::LowerAlpha => "a",
+            Numbering::UpperAlpha => "A",
+            Numbering::LowerRoman => "i",
+            Numbering::UpperRoman => "I",
+        }
+    }
+}
+
+impl Element<elements::A> {
+    /// Sets the link's URL, checked with the default `UrlSanitizer`.
+    pub fn href(self, url: &str) -> Self {
+        self.push(href(url))
+    }
+
+    pub fn target(self, target: &str) -> Self {
+        self.attribute("target", target)
+    }
+
+    pub fn rel(self, rel: &str) -> Self {
+        self.attribute("rel", rel)
+    }
+}
+
+impl Element<elements::Img> {
+    pub fn src(self, url: &str) -> Self {
+        self.push(src(url))
+    }
+
+    pub fn alt(self, alt: &str) -> Self {
+        self.attribute("alt", alt)
+    }
+
+    pub fn width(self, width: u32) -> Self {
+        self.attribute("width", &width.to_string())
+    }
+
+    pub fn height(self, height: u32) -> Self {
+        self.attribute("height", &height.to_string())
+    }
+}
+
+impl Element<elements::Ol> {
+    /// The number of the first item.
+    pub fn start(self, start: usize) -> Self {
+        self.attribute("start", &start.to_string())
+    }
+
+    pub fn numbering(self, numbering: Numbering) -> Self {
+        self.attribute("type", numbering.as_str())
+    }
+
+    pub fn reversed(self, reversed: bool) -> Self {
+        self.flag("reversed", reversed)
+    }
+}
+
+impl Element<elements::Li> {
+    /// The item's number, in an `ol`.
+    pub fn value(self, value: usize) -> Self {
+        self.attribute("value", &value.to_string())
+    }
+}
+
+impl Element<elements::Details> {
+    pub fn open(self, open: bool) -> Self {
+        self.flag("open", open)
+    }
+}
+
+impl Element<elements::Blockquote> {
+    /// Sets the URL of the quote's source, checked with the default `UrlSanitizer`.
+    pub fn cite(self, url: &str) -> Self {
+        self.push(attributes::cite(url))
+    }
+}
+
+impl Element<elements::Q> {
+    /// Sets the URL of the quote's source, checked with the default `UrlSanitizer`.
+    pub fn cite(self, url: &str) -> Self {
+        self.push(attributes::cite(url))
+    }
+}
+
+impl Element<elements::Td> {
+    pub fn colspan(self, columns: usize) -> Self {
+        self.attribute("colspan", &columns.to_string())
+    }
+
+    pub fn rowspan(self, rows: usize) -> Self {
+        self.attribute("rowspan", &rows.to_string())
+    }
+}
+
+impl Element<elements::Th> {
+    pub fn colspan(self, columns: usize) -> Self {
+        self.attribute("colspan", &columns.to_string())
+    }
+
+    pub fn rowspan(self, rows: usize) -> Self {
+        self.attribute("rowspan", &rows.to_string())
+    }
+}
+
+impl Element<elements::Time> {
+    pub fn datetime(self, datetime: &str) -> Self {
+        self.attribute("datetime", datetime)
+    }
+}
+
+impl Element<elements::Label> {
+    /// The ID of the control the label is for.
+    pub fn for_id(self, id: &str) -> Self {
+        self.attribute("for", id)
+    }
+}
+
+impl Element<elements::Input> {
+    pub fn input_type(self, input_type: &str) -> Self {
+        self.attribute("type", input_type)
+    }
+
+    pub fn name(self, name: &str) -> Self {
+        self.attribute("name", name)
+    }
+
+    pub fn value(self, value: &str) -> Self {
+        self.attribute("value", value)
+    }
+
+    pub fn placeholder(self, placeholder: &str) -> Self {
+        self.attribute("placeholder", placeholder)
+    }
+
+    pub fn checked(self, checked: bool) -> Self {
+        self.flag("checked", checked)
+    }
+
+    pub fn disabled(self, disabled: bool) -> Self {
+        self.flag("disabled", disabled)
+    }
+}
+
+impl Element<elements::Button> {
+    pub fn disabled(self, disabled: bool) -> Self {
+        self.flag("disabled", disabled)
+    }
+}
+
+impl Element<elements::Select> {
+    pub fn name(self, name: &str) -> Self {
+        self.attribute("name", name)
+    }
+
+    pub fn disabled(self, disabled: bool) -> Self {
+        self.flag("disabled", disabled)
+    }
+}
+
+impl Element<elements::Option> {
+    pub fn value(self, value: &str) -> Self {
+        self.attribute("value", value)
+    }
+
+    pub fn selected(self, selected: bool) -> Self {
+        self.flag("selected", selected)
+    }
+}
+
+impl Element<elements::Textarea> {
+    pub fn name(self, name: &str) -> Self {
+        self.attribute("name", name)
+    }
+
+    pub fn placeholder(self, placeholder: &str) -> Self {
+        self.attribute("placeholder", placeholder)
+    }
+
+    pub fn disabled(self, disabled: bool) -> Self {
+        self.flag("disabled", disabled)
+    }
+}
+
+impl Element<elements::Iframe> {
+    pub fn src(self, url: &str) -> Self {
+        self.push(src(url))
+    }
+}
+
+impl Element<elements::Audio> {
+    pub fn src(self, url: &str) -> Self {
+        self.push(src(url))
+    }
+
+    pub fn controls(self, controls: bool) -> Self {
+        self.flag("controls", controls)
+    }
+}
+
+impl Element<elements::Video> {
+    pub fn src(self, url: &str) -> Self {
+        self.push(src(url))
+    }
+
+    pub fn controls(self, controls: bool) -> Self {
+        self.flag("controls", controls)
+    }
+}
+
+impl Element<elements::Source> {
+    pub fn src(self, url: &str) -> Self {
+        self.push(src(url))
+    }
+
+    pub fn media_type(self, media_type: &str) -> Self {
+        self.attribute("type", media_type)
+    }
+}
+
+impl Element<elements::Embed> {
+    pub fn src(self, url: &str) -> Self {
+        self.push(src(url))
+    }
+}
@@ -1,2 +1,3 @@
 pub mod attributes;
+pub mod html;
 pub mod tags;
@@ -1,48 +1,195 @@
 use crate::attributes::Attribute;
-use regex::{Regex, Captures};
+use std::borrow::Cow;
+use std::fmt;
 
-#[derive(Debug)]
-pub struct Tag(String);
+/// The syntax a tag is written out in. They only differ in void elements, which are written as
+/// `<br>` in HTML and `<br/>` in XHTML, and boolean attributes, which are written as `checked` in
+/// HTML and `checked="checked"` in XHTML.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Syntax {
+    Html,
+    Xhtml,
+}
 
-pub type TagType = fn(a: Vec<Attribute>, b: Vec<Tag>) -> Tag;
+/// A piece of markup. Tags are ropes: composing them moves the children in as they are instead of
+/// copying their text, so building up a page is linear in the size of the output. The text is only
+/// put together when the tag is written out with `Display` (eg. `to_string`) or `write_to`.
+#[derive(Debug, Clone, Default)]
+pub struct Tag {
+    segments: Vec<Segment>,
+    /// The length of the tag's text as HTML, in bytes.
+    len: usize,
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Str(Cow<'static, str>),
+    Tag(Tag),
+    /// The end of a void element's start tag.
+    VoidEnd,
+    /// The name of a boolean attribute.
+    Boolean(String),
+}
 
 impl Tag {
-    pub fn to_string(&self) -> String {
-        match self {
-            Tag(str) => str.clone(),
+    /// The length of the tag's text as HTML, in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Writes the tag's text as HTML into `out`, without putting it together into a single string
+    /// first.
+    pub fn write_to<W: fmt::Write + ?Sized>(&self, out: &mut W) -> fmt::Result {
+        self.write_with(out, Syntax::Html)
+    }
+
+    /// Writes the tag's text into `out` in the given syntax.
+    pub fn write_with<W: fmt::Write + ?Sized>(&self, out: &mut W, syntax: Syntax) -> fmt::Result {
+        for segment in &self.segments {
+            match (segment, syntax) {
+                (Segment::Str(str), _) => out.write_str(str)?,
+                (Segment::Tag(tag), _) => tag.write_with(out, syntax)?,
+                (Segment::VoidEnd, Syntax::Html) => out.write_str(">")?,
+                (Segment::VoidEnd, Syntax::Xhtml) => out.write_str("/>")?,
+                (Segment::Boolean(name), Syntax::Html) => out.write_str(name)?,
+                (Segment::Boolean(name), Syntax::Xhtml) => write!(out, "{}=\"{}\"", name, name)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Puts the tag's text together as XHTML. Use `to_string` for HTML.
+    pub fn to_xhtml(&self) -> String {
+        let mut result = String::with_capacity(self.len);
+        // Writing into a `String` can't fail.
+        let _ = self.write_with(&mut result, Syntax::Xhtml);
+        result
+    }
+
+    pub(crate) fn push_str<S: Into<Cow<'static, str>>>(&mut self, str: S) {
+        let str = str.into();
+        if !str.is_empty() {
+            self.len += str.len();
+            self.segments.push(Segment::Str(str));
+        }
+    }
+
+    pub(crate) fn push_tag(&mut self, mut tag: Tag) {
+        if tag.is_empty() {
+            return;
+        }
+
+        self.len += tag.len;
+        // Tags that are a single segment (eg. text) don't need to be nested.
+        if tag.segments.len() == 1 {
+            self.segments.append(&mut tag.segments);
+        } else {
+            self.segments.push(Segment::Tag(tag));
         }
     }
+
+    fn push_segment(&mut self, segment: Segment, len: usize) {
+        self.len += len;
+        self.segments.push(segment);
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_to(f)
+    }
+}
+
+impl From<Tag> for String {
+    fn from(tag: Tag) -> String {
+        let mut result = String::with_capacity(tag.len);
+        // Writing into a `String` can't fail.
+        let _ = tag.write_to(&mut result);
+        result
+    }
 }
 
+pub type TagType = fn(a: Vec<Attribute>, b: Vec<Tag>) -> Tag;
+
 pub fn collect(t: Vec<Tag>) -> Tag {
-    Tag(t
+    let mut result = empty();
+    for tag in t {
+        result.push_tag(tag);
+    }
+    result
+}
+
+/// What each byte is replaced with when escaping, as an index into `REPLACEMENTS`. 0 means the
+/// byte is kept as it is.
+static ESCAPES: [u8; 256] = {
+    let mut escapes = [0; 256];
+    escapes[b'&' as usize] = 1;
+    escapes[b'<' as usize] = 2;
+    escapes[b'>' as usize] = 3;
+    escapes[b'"' as usize] = 4;
+    escapes[b'\'' as usize] = 5;
+    escapes
+};
+
+static REPLACEMENTS: [&str; 6] = ["", "&amp;", "&lt;", "&gt;", "&quot;", "&apos;"];
+
+/// The index of the first byte that needs escaping. Bytes are checked a chunk at a time without
+/// branching, which the compiler can turn into SIMD, and only a chunk with something to escape in
+/// it is searched byte by byte.
+fn first_escape(bytes: &[u8]) -> Option<usize> {
+    const CHUNK: usize = 16;
+
+    let mut chunks = bytes.chunks_exact(CHUNK);
+    for (index, chunk) in chunks.by_ref().enumerate() {
+        if chunk.iter().fold(0, |found, &byte| found | ESCAPES[byte as usize]) != 0 {
+            let offset = chunk.iter().position(|&byte| ESCAPES[byte as usize] != 0);
+            return offset.map(|offset| index * CHUNK + offset);
+        }
+    }
+
+    let start = bytes.len() - chunks.remainder().len();
+    chunks
+        .remainder()
         .iter()
-        .map(|x| x.to_string())
-        .collect::<Vec<String>>()
-        .join(""))
-}
-
-fn escape(t: &str) -> String {
-    let re = Regex::new("(&|<|>|\"|')").unwrap();
-    let result = re.replace_all(t, |cap: &Captures| {
-        match &cap[0] {
-            "&" => "&amp;",
-            "<" => "&lt;",
-            ">" => "&gt;",
-            "\"" => "&quot;",
-            "'" => "&apos;",
-            _ => ""
+        .position(|&byte| ESCAPES[byte as usize] != 0)
+        .map(|offset| start + offset)
+}
+
+/// Escapes text so it can be put in HTML. The text is returned as it is, without copying it, if
+/// nothing in it needs escaping.
+pub fn escape(t: &str) -> Cow<'_, str> {
+    let first = match first_escape(t.as_bytes()) {
+        Some(first) => first,
+        None => return Cow::Borrowed(t),
+    };
+
+    let mut result = String::with_capacity(t.len() + t.len() / 8 + 8);
+    // The bytes that are escaped are all ASCII, so slicing around them is always on a char boundary.
+    let mut kept = 0;
+    for (index, &byte) in t.as_bytes().iter().enumerate().skip(first) {
+        let replacement = ESCAPES[byte as usize];
+        if replacement != 0 {
+            result.push_str(&t[kept..index]);
+            result.push_str(REPLACEMENTS[replacement as usize]);
+            kept = index + 1;
         }
-    }).to_string();
-    return result;
+    }
+    result.push_str(&t[kept..]);
+    Cow::Owned(result)
 }
 
 pub fn text(t: &str) -> Tag {
-    Tag(escape(t))
+    let mut result = empty();
+    result.push_str(escape(t).into_owned());
+    result
 }
 
 pub fn empty() -> Tag {
-    Tag(String::from(""))
+    Tag::default()
 }
 
 pub fn option_include(t: Option<Tag>) -> Tag {
@@ -52,41 +199,69 @@ pub fn option_include(t: Option<Tag>) -> Tag {
     }
 }
 
-fn join_attributes(attributes: Vec<Attribute>) -> String {
-    attributes
-        .iter()
-        .map(|x| x.to_string())
-        .collect::<Vec<String>>()
-        .join(" ")
+/// Whether an element is a void element, which can't have children and has no end tag.
+pub fn is_void(name: &str) -> bool {
+    matches!(
+        name,
+        "area" | "base" | "br" | "col" | "embed" | "hr" | "img" | "input" | "link" | "meta"
+            | "param" | "source" | "track" | "wbr"
+    )
 }
 
-fn join_children(tags: Vec<Tag>) -> String {
-    tags.iter()
-        .map(|x| x.to_string())
-        .collect::<Vec<String>>()
-        .join("")
-}
-
-pub fn tag(name: &str, attributes: Vec<Attribute>, children: Vec<Tag>) -> Tag {
-    let attributes = join_attributes(attributes);
-    let children = join_children(children);
+/// Builds an element out of its name, attributes and children. The attributes' and children's
+/// text is moved in rather than copied.
+///
+/// Elements are always written out, even if they're empty, and other elements always get an end
+/// tag. Void elements get no end tag, so their children (which they can't hold) are put after them.
+pub(crate) fn element(
+    name: Cow<'static, str>,
+    attributes: Vec<Attribute>,
+    children: Vec<Tag>,
+    void: bool,
+) -> Tag {
+    let mut result = empty();
+    result.push_str("<");
+    result.push_str(name.clone());
+    for attribute in attributes {
+        result.push_str(" ");
+        if attribute.is_boolean() {
+            let name = attribute.into_string();
+            let len = name.len();
+            result.push_segment(Segment::Boolean(name), len);
+        } else {
+            result.push_str(attribute.into_string());
+        }
+    }
 
-    if attributes.len() > 0 && children.len() > 0 {
-        return Tag(["<", &name, " ", &attributes, ">", &children, "</", &name, ">"].concat())
+    if void {
+        result.push_segment(Segment::VoidEnd, 1);
+    } else {
+        result.push_str(">");
+    }
+    for child in children {
+        result.push_tag(child);
     }
-    if attributes.len() > 0 {
-        return Tag(["<", &name, " ", &attributes, "/>"].concat());
+    if !void {
+        result.push_str("</");
+        result.push_str(name);
+        result.push_str(">");
     }
-    if children.len() > 0 {
-        return Tag(["<", &name, ">", &children, "</", &name, ">"].concat());
-    } 
-    return empty();
+    result
+}
+
+fn build(name: Cow<'static, str>, attributes: Vec<Attribute>, children: Vec<Tag>) -> Tag {
+    let void = is_void(&name);
+    element(name, attributes, children, void)
+}
+
+pub fn tag(name: &str, attributes: Vec<Attribute>, children: Vec<Tag>) -> Tag {
+    build(Cow::Owned(name.to_string()), attributes, children)
 }
 
 macro_rules! t {
     ($x:ident) => {
         pub fn $x(attributes: Vec<Attribute>, children: Vec<Tag>) -> Tag {
-            tag(stringify!($x), attributes, children)
+            build(Cow::Borrowed(stringify!($x)), attributes, children)
         }
     }
 }
@@ -119,7 +294,7 @@ t!(dl);
 t!(dt);
 
 pub fn img(attributes: Vec<Attribute>) -> Tag {
-    tag("img", attributes, vec![])
+    build(Cow::Borrowed("img"), attributes, vec![])
 }
 
 t!(iframe);
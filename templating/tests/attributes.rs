@@ -0,0 +1,120 @@
+use templating::attributes::{attribute, cite, href, href_with, src, UrlSanitizer, BLOCKED_URL};
+use templating::html;
+use templating::tags::a;
+
+#[test]
+fn escapes_attribute_values() {
+    assert_eq!(
+        attribute("title", r#"a "quoted" <b> & 'c'"#).to_string(),
+        r#"title="a &quot;quoted&quot; &lt;b&gt; &amp; &apos;c&apos;""#
+    );
+    assert_eq!(
+        html::div()
+            .data("block-type", r#""><script>"#)
+            .build()
+            .to_string(),
+        r#"<div data-block-type="&quot;&gt;&lt;script&gt;"></div>"#
+    );
+}
+
+#[test]
+fn allows_safe_urls() {
+    let sanitizer = UrlSanitizer::default();
+    for url in [
+        "https://example.com/a?b=c",
+        "HTTP://EXAMPLE.COM",
+        "mailto:someone@example.com",
+        "tel:+1-555-0100",
+        "/relative/page",
+        "./a:b",
+        "page?time=12:00",
+        "#heading",
+        "",
+    ] {
+        assert!(sanitizer.is_allowed(url), "{:?} should be allowed", url);
+        assert_eq!(sanitizer.sanitize(url), url);
+    }
+}
+
+#[test]
+fn blocks_unsafe_urls() {
+    let sanitizer = UrlSanitizer::default();
+    for url in [
+        "javascript:alert(1)",
+        "JaVaScRiPt:alert(1)",
+        " javascript:alert(1)",
+        "\u{0}javascript:alert(1)",
+        "\u{1f}\njavascript:alert(1)",
+        "java\tscript:alert(1)",
+        "java\nscript:alert(1)",
+        "vbscript:msgbox(1)",
+        "data:text/html;base64,PHNjcmlwdD5hbGVydCgxKTwvc2NyaXB0Pg==",
+        "file:///etc/passwd",
+    ] {
+        assert!(!sanitizer.is_allowed(url), "{:?} should be blocked", url);
+        assert_eq!(sanitizer.sanitize(url), BLOCKED_URL);
+    }
+
+    // Character references are decoded in attributes, so the value is escaped to keep them as
+    // they are in the URL.
+    assert_eq!(
+        href("jav&#x09;ascript:alert(1)").to_string(),
+        r#"href="jav&amp;#x09;ascript:alert(1)""#
+    );
+}
+
+#[test]
+fn allows_configured_schemes() {
+    let sanitizer = UrlSanitizer::with_schemes(vec!["https"]).allow("notion");
+    assert!(sanitizer.is_allowed("notion://www.notion.so/page"));
+    assert!(sanitizer.is_allowed("https://example.com"));
+    assert!(!sanitizer.is_allowed("http://example.com"));
+    assert_eq!(
+        href_with("http://example.com", &sanitizer).to_string(),
+        r##"href="#""##
+    );
+}
+
+#[test]
+fn sanitizes_links_and_sources() {
+    let link = a(
+        vec![href(r#"https://example.com/"><script>alert(1)</script>"#)],
+        vec![],
+    );
+    assert_eq!(
+        link.to_string(),
+        r#"<a href="https://example.com/&quot;&gt;&lt;script&gt;alert(1)&lt;/script&gt;"></a>"#
+    );
+    assert_eq!(src("javascript:alert(1)").to_string(), r##"src="#""##);
+    assert_eq!(
+        html::a()
+            .href("javascript:alert(1)")
+            .text("x")
+            .build()
+            .to_string(),
+        r##"<a href="#">x</a>"##
+    );
+    assert_eq!(
+        html::img()
+            .src("data:image/svg+xml,<svg/>")
+            .build()
+            .to_string(),
+        r##"<img src="#">"##
+    );
+    assert_eq!(cite("javascript:alert(1)").to_string(), r##"cite="#""##);
+    assert_eq!(
+        html::blockquote()
+            .cite("javascript:alert(1)")
+            .text("x")
+            .build()
+            .to_string(),
+        r##"<blockquote cite="#">x</blockquote>"##
+    );
+    assert_eq!(
+        html::q()
+            .cite("https://example.com/a?b=c&d=e")
+            .build()
+            .to_string(),
+        r#"<q cite="https://example.com/a?b=c&amp;d=e"></q>"#
+    );
+}
@@ -0,0 +1,174 @@
+use std::borrow::Cow;
+use templating::attributes::{boolean, class, href};
+use templating::html;
+use templating::tags::{self, is_void, tag, text, Tag};
+
+fn both(tag: Tag) -> (String, String) {
+    (tag.to_string(), tag.to_xhtml())
+}
+
+#[test]
+fn writes_void_elements_without_an_end_tag() {
+    assert_eq!(
+        both(tags::hr(vec![class("divider")], vec![])),
+        (
+            r#"<hr class="divider">"#.to_string(),
+            r#"<hr class="divider"/>"#.to_string()
+        )
+    );
+    assert_eq!(
+        both(tags::br(vec![], vec![])),
+        ("<br>".to_string(), "<br/>".to_string())
+    );
+    assert_eq!(
+        both(tags::img(vec![class("image")])),
+        (
+            r#"<img class="image">"#.to_string(),
+            r#"<img class="image"/>"#.to_string()
+        )
+    );
+    assert_eq!(tag("meta", vec![], vec![]).to_string(), "<meta>");
+
+    // Void elements can't hold children, so they go after it.
+    assert_eq!(tags::hr(vec![], vec![text("a")]).to_string(), "<hr>a");
+
+    for name in [
+        "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param",
+        "source", "track", "wbr",
+    ] {
+        assert!(is_void(name), "{} should be void", name);
+    }
+    for name in ["div", "p", "td", "script", "textarea", "summary"] {
+        assert!(!is_void(name), "{} shouldn't be void", name);
+    }
+}
+
+#[test]
+fn writes_empty_elements_in_full() {
+    assert_eq!(
+        both(tags::div(vec![], vec![])),
+        ("<div></div>".to_string(), "<div></div>".to_string())
+    );
+    assert_eq!(tags::td(vec![], vec![]).to_string(), "<td></td>");
+    assert_eq!(tags::p(vec![], vec![text("")]).to_string(), "<p></p>");
+    assert_eq!(
+        both(tags::p(vec![class("notion-text")], vec![])),
+        (
+            r#"<p class="notion-text"></p>"#.to_string(),
+            r#"<p class="notion-text"></p>"#.to_string()
+        )
+    );
+    assert_eq!(
+        tag("custom-element", vec![], vec![]).to_string(),
+        "<custom-element></custom-element>"
+    );
+}
+
+#[test]
+fn writes_elements_with_children() {
+    let link = tags::a(
+        vec![class("link"), href("/page")],
+        vec![tags::b(vec![], vec![text("a < b")])],
+    );
+    assert_eq!(
+        both(tags::li(vec![], vec![link])),
+        (
+            r#"<li><a class="link" href="/page"><b>a &lt; b</b></a></li>"#.to_string(),
+            r#"<li><a class="link" href="/page"><b>a &lt; b</b></a></li>"#.to_string()
+        )
+    );
+}
+
+#[test]
+fn writes_boolean_attributes() {
+    let details = tags::details(
+        vec![boolean("open")],
+        vec![tags::summary(vec![], vec![text("More")])],
+    );
+    assert_eq!(
+        both(details),
+        (
+            "<details open><summary>More</summary></details>".to_string(),
+            r#"<details open="open"><summary>More</summary></details>"#.to_string()
+        )
+    );
+    assert_eq!(
+        both(tags::input(
+            vec![boolean("checked"), boolean("disabled")],
+            vec![]
+        )),
+        (
+            "<input checked disabled>".to_string(),
+            r#"<input checked="checked" disabled="disabled"/>"#.to_string()
+        )
+    );
+}
+
+#[test]
+fn builds_elements_the_same_way() {
+    assert_eq!(
+        both(html::hr().class("divider").build()),
+        (
+            r#"<hr class="divider">"#.to_string(),
+            r#"<hr class="divider"/>"#.to_string()
+        )
+    );
+    assert_eq!(html::div().build().to_string(), "<div></div>");
+
+    let list = html::ol()
+        .class("list")
+        .start(3)
+        .numbering(html::Numbering::LowerAlpha)
+        .child(html::li().text("c"))
+        .child(html::li().child(html::input().input_type("checkbox").checked(true)));
+    assert_eq!(
+        both(list.build()),
+        (
+            r#"<ol class="list" start="3" type="a"><li>c</li><li><input type="checkbox" checked></li></ol>"#.to_string(),
+            r#"<ol class="list" start="3" type="a"><li>c</li><li><input type="checkbox" checked="checked"/></li></ol>"#
+                .to_string()
+        )
+    );
+}
+
+#[test]
+fn keeps_track_of_the_length() {
+    let tags = [
+        tags::div(vec![], vec![]),
+        tags::hr(vec![class("x")], vec![]),
+        tags::input(vec![boolean("checked")], vec![]),
+        html::details().open(true).text("a").build(),
+    ];
+    for tag in tags {
+        assert_eq!(tag.len(), tag.to_string().len());
+    }
+}
+
+#[test]
+fn escapes_text() {
+    assert_eq!(
+        tags::escape(r#"a & b < c > d " e ' f"#),
+        "a &amp; b &lt; c &gt; d &quot; e &apos; f"
+    );
+    assert_eq!(tags::escape("&&"), "&amp;&amp;");
+    assert_eq!(tags::escape("é <ü> ✓"), "é &lt;ü&gt; ✓");
+    assert!(matches!(
+        tags::escape("nothing to escape here, at all"),
+        Cow::Borrowed(_)
+    ));
+    assert!(matches!(tags::escape(""), Cow::Borrowed(_)));
+
+    // Around and across the ends of the chunks that are checked at once.
+    for length in 0..40 {
+        for position in 0..length {
+            let mut input = "x".repeat(length);
+            input.replace_range(position..position + 1, "<");
+            let expected = format!("{}&lt;{}", &input[..position], &input[position + 1..]);
+            assert_eq!(tags::escape(&input), expected);
+        }
+        assert!(matches!(
+            tags::escape(&"é".repeat(length)),
+            Cow::Borrowed(_)
+        ));
+    }
+}
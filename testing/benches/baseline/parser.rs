@@ -0,0 +1,623 @@
+//! The parser as it was before it borrowed strings from its input: every string is an owned
+//! `String`, and blocks that don't match the typings are parsed a second time as raw JSON by the
+//! untagged `Either`. Only kept so the `parse` benchmarks can compare against it.
+#![allow(dead_code)]
+
+use base::id::BlockId;
+use anyhow::Result;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum YesOrNo {
+    Yes,
+    No,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TodoProperties {
+    pub title: Value,
+    pub checked: Vec<Vec<YesOrNo>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ColumnFormat {
+    pub column_ratio: f64,
+}
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ImageProperties {
+    pub source: Vec<Vec<String>>,
+    pub caption: Option<Vec<Vec<String>>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ImageFormat {
+    pub block_width: i64,
+    pub block_height: i64,
+    pub display_source: Option<i64>,
+    pub block_full_width: Option<bool>,
+    pub block_page_width: Option<bool>,
+    pub block_aspect_ratio: f64,
+    pub block_preserve_scale: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PageFormat {
+    pub page_full_width: Option<bool>,
+    pub page_small_text: Option<bool>,
+    pub page_cover_position: Option<f64>,
+    pub block_locked: Option<bool>,
+    pub page_cover: Option<String>,
+    pub page_icon: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PagePermissions {
+    pub role: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FigmaProperties {
+    pub source: Option<Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FigmaFormat {
+    pub block_height: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TextProperties {
+    pub title: Vec<FormattedText>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PageProperties {
+    pub title: Vec<FormattedText>,
+}
+
+/// The type-specific part of a block, tagged by the block's `type` field.
+///
+/// Block types the parser doesn't know about yet are kept as `Unknown`, holding the type name and
+/// the raw JSON of the block's type-specific fields, so the block's children can still be rendered
+/// and the block serializes back into its original form. Blocks of a known type that don't match
+/// that type's typings still fail to parse.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(remote = "Self", tag = "type", rename_all = "snake_case")]
+pub enum RootBlockType {
+    Text {
+        properties: Option<TextProperties>,
+    },
+    BulletedList {
+        properties: Option<TextProperties>,
+    },
+    NumberedList {
+        properties: Option<TextProperties>,
+    },
+    Toggle {
+        properties: Option<TextProperties>,
+    },
+    Header {
+        properties: Option<TextProperties>,
+    },
+    SubHeader {
+        properties: Option<TextProperties>,
+    },
+    SubSubHeader {
+        properties: Option<TextProperties>,
+    },
+    Quote {
+        properties: Option<TextProperties>,
+    },
+    ToDo {
+        properties: Option<TodoProperties>,
+    },
+    Divider,
+    ColumnList,
+    Column {
+        format: ColumnFormat,
+    },
+    Image {
+        properties: ImageProperties,
+        format: ImageFormat,
+        file_ids: Vec<String>,
+    },
+    Page {
+        format: Option<PageFormat>,
+        file_ids: Option<Vec<String>>,
+        properties: PageProperties,
+    },
+    Figma {
+        properties: Option<FigmaProperties>,
+        format: Option<FigmaFormat>,
+    },
+    #[serde(skip)]
+    Unknown { type_name: String, raw: Value },
+}
+
+/// The `type` names of every block type in `RootBlockType`, except for `Unknown`.
+const KNOWN_BLOCK_TYPES: &[&str] = &[
+    "text",
+    "bulleted_list",
+    "numbered_list",
+    "toggle",
+    "header",
+    "sub_header",
+    "sub_sub_header",
+    "quote",
+    "to_do",
+    "divider",
+    "column_list",
+    "column",
+    "image",
+    "page",
+    "figma",
+];
+
+impl RootBlockType {
+    /// Returns the block's Notion type name (the `type` field in the JSON, eg. `bulleted_list`).
+    pub fn type_name(&self) -> &str {
+        match self {
+            RootBlockType::Text { .. } => "text",
+            RootBlockType::BulletedList { .. } => "bulleted_list",
+            RootBlockType::NumberedList { .. } => "numbered_list",
+            RootBlockType::Toggle { .. } => "toggle",
+            RootBlockType::Header { .. } => "header",
+            RootBlockType::SubHeader { .. } => "sub_header",
+            RootBlockType::SubSubHeader { .. } => "sub_sub_header",
+            RootBlockType::Quote { .. } => "quote",
+            RootBlockType::ToDo { .. } => "to_do",
+            RootBlockType::Divider => "divider",
+            RootBlockType::ColumnList => "column_list",
+            RootBlockType::Column { .. } => "column",
+            RootBlockType::Image { .. } => "image",
+            RootBlockType::Page { .. } => "page",
+            RootBlockType::Figma { .. } => "figma",
+            RootBlockType::Unknown { type_name, raw: _ } => type_name,
+        }
+    }
+}
+
+impl Serialize for RootBlockType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            RootBlockType::Unknown { type_name: _, raw } => raw.serialize(serializer),
+            _ => RootBlockType::serialize(self, serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for RootBlockType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = Value::deserialize(deserializer)?;
+        let type_name = raw.get("type").and_then(Value::as_str).unwrap_or("");
+
+        if KNOWN_BLOCK_TYPES.contains(&type_name) {
+            RootBlockType::deserialize(raw).map_err(de::Error::custom)
+        } else {
+            Ok(RootBlockType::Unknown {
+                type_name: type_name.to_string(),
+                raw,
+            })
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BlockFormatType {
+    pub block_color: Option<ColorType>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BaseValueType {
+    pub id: BlockId,
+    pub version: i64,
+    pub created_time: i64,
+    pub last_edited_time: i64,
+    pub parent_id: BlockId,
+    pub parent_table: String,
+    pub alive: bool,
+    pub created_by_table: String,
+    pub created_by_id: String,
+    pub last_edited_by_table: String,
+    pub last_edited_by_id: String,
+    pub shard_id: Option<i64>,
+    pub space_id: Option<String>,
+    pub content: Option<Vec<BlockId>>,
+    pub format: Option<BlockFormatType>,
+    #[serde(flatten)]
+    pub block: RootBlockType,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorType {
+    Gray,
+    Brown,
+    Orange,
+    Yellow,
+    Teal,
+    Blue,
+    Purple,
+    Pink,
+    Red,
+    GrayBackground,
+    BrownBackground,
+    OrangeBackground,
+    YellowBackground,
+    TealBackground,
+    BlueBackground,
+    PurpleBackground,
+    PinkBackground,
+    RedBackground,
+    None,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum NoContextFormat {
+    Bold,
+    Italic,
+    Strike,
+    Underline,
+    Code,
+    None,
+}
+
+impl Serialize for NoContextFormat {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(match *self {
+            NoContextFormat::Bold => "b",
+            NoContextFormat::Italic => "i",
+            NoContextFormat::Strike => "s",
+            NoContextFormat::Underline => "_",
+            NoContextFormat::Code => "c",
+            NoContextFormat::None => "",
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for NoContextFormat {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        match s.as_str() {
+            "i" => Ok(NoContextFormat::Italic),
+            "b" => Ok(NoContextFormat::Bold),
+            "s" => Ok(NoContextFormat::Strike),
+            "_" => Ok(NoContextFormat::Underline),
+            "c" => Ok(NoContextFormat::Code),
+            _ => Ok(NoContextFormat::None),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum IntermediaryFormatEnum {
+    Main(Either<[NoContextFormat; 1], ContextFormat>),
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(untagged)]
+pub enum IntermediaryContextFormattingRepresentation {
+    Main((String, Value)),
+}
+
+/// A format that needs a second value to render (eg. a link needs its URL). Serializes back into
+/// Notion's `["a", "https://..."]` form. Formats the parser doesn't know about (eg. mentions,
+/// dates, and comments) are kept in `Unknown` with their raw value, so they survive a round trip.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(
+    from = "IntermediaryContextFormattingRepresentation",
+    into = "IntermediaryContextFormattingRepresentation"
+)]
+pub enum ContextFormat {
+    Link(String),
+    Highlight(ColorType),
+    Unknown(String, Value),
+}
+
+pub fn from_color(c: &str) -> ColorType {
+    match c {
+        "gray" => ColorType::Gray,
+        "brown" => ColorType::Brown,
+        "orange" => ColorType::Orange,
+        "yellow" => ColorType::Yellow,
+        "teal" => ColorType::Teal,
+        "blue" => ColorType::Blue,
+        "purple" => ColorType::Purple,
+        "pink" => ColorType::Pink,
+        "red" => ColorType::Red,
+        "gray_background" => ColorType::GrayBackground,
+        "brown_background" => ColorType::BrownBackground,
+        "orange_background" => ColorType::OrangeBackground,
+        "yellow_background" => ColorType::YellowBackground,
+        "teal_background" => ColorType::TealBackground,
+        "blue_background" => ColorType::BlueBackground,
+        "purple_background" => ColorType::PurpleBackground,
+        "pink_background" => ColorType::PinkBackground,
+        "red_background" => ColorType::RedBackground,
+        _ => ColorType::None,
+    }
+}
+
+/// The reverse of `from_color`. `ColorType::None` becomes Notion's `default` color.
+pub fn to_color(c: &ColorType) -> &'static str {
+    match c {
+        ColorType::Gray => "gray",
+        ColorType::Brown => "brown",
+        ColorType::Orange => "orange",
+        ColorType::Yellow => "yellow",
+        ColorType::Teal => "teal",
+        ColorType::Blue => "blue",
+        ColorType::Purple => "purple",
+        ColorType::Pink => "pink",
+        ColorType::Red => "red",
+        ColorType::GrayBackground => "gray_background",
+        ColorType::BrownBackground => "brown_background",
+        ColorType::OrangeBackground => "orange_background",
+        ColorType::YellowBackground => "yellow_background",
+        ColorType::TealBackground => "teal_background",
+        ColorType::BlueBackground => "blue_background",
+        ColorType::PurpleBackground => "purple_background",
+        ColorType::PinkBackground => "pink_background",
+        ColorType::RedBackground => "red_background",
+        ColorType::None => "default",
+    }
+}
+
+impl From<IntermediaryContextFormattingRepresentation> for ContextFormat {
+    fn from(t: IntermediaryContextFormattingRepresentation) -> Self {
+        let IntermediaryContextFormattingRepresentation::Main((a, b)) = t;
+        match (a.as_str(), b) {
+            ("a", Value::String(link)) => ContextFormat::Link(link),
+            ("h", Value::String(color)) => ContextFormat::Highlight(from_color(&color)),
+            (_, b) => ContextFormat::Unknown(a, b),
+        }
+    }
+}
+
+impl From<ContextFormat> for IntermediaryContextFormattingRepresentation {
+    fn from(t: ContextFormat) -> Self {
+        let pair = match t {
+            ContextFormat::Link(link) => (String::from("a"), Value::String(link)),
+            ContextFormat::Highlight(color) => {
+                (String::from("h"), Value::String(to_color(&color).to_string()))
+            }
+            ContextFormat::Unknown(a, b) => (a, b),
+        };
+        IntermediaryContextFormattingRepresentation::Main(pair)
+    }
+}
+
+/// A single piece of formatting on a `FormattedText`. Serializes back into Notion's `["b"]`
+/// or `["a", "https://..."]` form.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(from = "IntermediaryFormatEnum", into = "IntermediaryFormatEnum")]
+pub enum FormatType {
+    NoContext(NoContextFormat),
+    Context(ContextFormat),
+}
+
+impl From<IntermediaryFormatEnum> for FormatType {
+    fn from(t: IntermediaryFormatEnum) -> Self {
+        let IntermediaryFormatEnum::Main(s) = t;
+        match s {
+            Either::Left(l) => FormatType::NoContext(l[0]),
+            Either::Right(l) => FormatType::Context(l),
+        }
+    }
+}
+
+impl From<FormatType> for IntermediaryFormatEnum {
+    fn from(t: FormatType) -> Self {
+        match t {
+            FormatType::NoContext(l) => IntermediaryFormatEnum::Main(Either::Left([l])),
+            FormatType::Context(r) => IntermediaryFormatEnum::Main(Either::Right(r)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(untagged)]
+enum IntermediaryFormattingRepresentation {
+    Main(Either<Vec<String>, (String, Vec<FormatType>)>),
+}
+
+/// A run of text and its formatting. Serializes back into Notion's `["text"]` or
+/// `["text", [["b"], ["a", "https://..."]]]` form.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(
+    from = "IntermediaryFormattingRepresentation",
+    into = "IntermediaryFormattingRepresentation"
+)]
+pub struct FormattedText {
+    pub text: String,
+    pub formatting: Option<Vec<FormatType>>,
+}
+
+impl From<IntermediaryFormattingRepresentation> for FormattedText {
+    fn from(text: IntermediaryFormattingRepresentation) -> Self {
+        let IntermediaryFormattingRepresentation::Main(s) = text;
+        match s {
+            Either::Left(r) => FormattedText {
+                text: r[0].clone(),
+                formatting: None,
+            },
+            Either::Right((text, format)) => FormattedText {
+                text,
+                formatting: Some(format),
+            },
+        }
+    }
+}
+
+impl From<FormattedText> for IntermediaryFormattingRepresentation {
+    fn from(text: FormattedText) -> Self {
+        match text.formatting {
+            Some(format) => IntermediaryFormattingRepresentation::Main(Either::Right((text.text, format))),
+            None => IntermediaryFormattingRepresentation::Main(Either::Left(vec![text.text])),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(untagged)]
+pub enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BlockType {
+    pub role: String,
+    pub value: Either<BaseValueType, Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NotionUserValueType {
+    pub id: String,
+    pub version: i64,
+    pub email: String,
+    pub given_name: String,
+    pub family_name: String,
+    pub profile_photo: String,
+    pub pubonboarding_complete: Option<bool>,
+    pub mobile_onboarding_complete: Option<bool>,
+    pub clipper_onboarding_complete: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NotionUserType {
+    pub role: String,
+    pub value: NotionUserValueType,
+}
+
+/// A workspace. `pages` holds the IDs of the workspace's top-level pages, in sidebar order.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SpaceValue {
+    pub id: String,
+    pub version: i64,
+    pub name: String,
+    pub icon: Option<String>,
+    pub domain: Option<String>,
+    pub plan_type: Option<String>,
+    #[serde(default)]
+    pub pages: Vec<BlockId>,
+    pub created_time: Option<i64>,
+    pub created_by_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SpaceType {
+    pub role: String,
+    pub value: SpaceValue,
+}
+
+/// A user's view of a workspace they belong to, holding their own bookmarks and shared pages.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SpaceViewValue {
+    pub id: String,
+    pub version: i64,
+    pub space_id: String,
+    pub parent_id: String,
+    pub parent_table: String,
+    pub alive: bool,
+    pub joined: Option<bool>,
+    pub bookmarked_pages: Option<Vec<BlockId>>,
+    pub shared_pages: Option<Vec<BlockId>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SpaceViewType {
+    pub role: String,
+    pub value: SpaceViewValue,
+}
+
+pub type BlockTableType = HashMap<BlockId, BlockType>;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RecordMapType {
+    #[serde(default)]
+    pub block: BlockTableType,
+    #[serde(default)]
+    pub notion_user: HashMap<String, NotionUserType>,
+    #[serde(default)]
+    pub space: HashMap<String, SpaceType>,
+    #[serde(default)]
+    pub space_view: HashMap<String, SpaceViewType>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadPageChunkData {
+    pub record_map: RecordMapType,
+}
+
+pub fn parse(input: String) -> Result<LoadPageChunkData> {
+    let result: LoadPageChunkData = serde_json::from_str(&input)?;
+    Ok(result)
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadUserContentData {
+    pub record_map: RecordMapType,
+}
+
+pub fn parse_user_content(input: String) -> Result<LoadUserContentData> {
+    let result: LoadUserContentData = serde_json::from_str(&input)?;
+    Ok(result)
+}
+
+/// The response of `getSpaces`: a record map for each user the token is logged in as, keyed by user ID.
+pub type GetSpacesData = HashMap<String, RecordMapType>;
+
+pub fn parse_spaces(input: String) -> Result<GetSpacesData> {
+    let result: GetSpacesData = serde_json::from_str(&input)?;
+    Ok(result)
+}
+
+/// The snippet Notion returns for a search hit. Matched terms are wrapped in Notion's own
+/// marker tags (eg. `<gzkNfoUU>term</gzkNfoUU>`), which are left as-is.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHighlightType {
+    pub text: Option<String>,
+    pub path_text: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResultType {
+    pub id: BlockId,
+    pub is_navigable: Option<bool>,
+    pub score: Option<f64>,
+    pub highlight: Option<SearchHighlightType>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchData {
+    pub results: Vec<SearchResultType>,
+    pub total: i64,
+    pub record_map: RecordMapType,
+}
+
+pub fn parse_search(input: String) -> Result<SearchData> {
+    let result: SearchData = serde_json::from_str(&input)?;
+    Ok(result)
+}
@@ -1,23 +1,89 @@
-use base::parser::parse;
+use base::id::BlockId;
+use base::parser::{parse, parse_owned, parse_reader};
 use base::renderer::Renderer;
-use std::fs;
+use templating::tags::escape;
 use ui::{Blocks, Inline, Wrapper};
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
 
-fn benchmark() {
-    let json = fs::read_to_string("src.json").unwrap();
+#[path = "baseline/parser.rs"]
+mod baseline_parser;
+mod fixture;
+
+fn benchmark(json: &str) {
     let result = parse(json).unwrap();
+    let id = BlockId::parse(fixture::ROOT_ID).unwrap();
     let renderer = Renderer::new(&result.record_map.block, Blocks {}, Inline {}, Wrapper {});
-    let html = renderer
-        .render("ddda599f-ff69-4974-9dec-86f6abf3209a")
+    renderer
+        .render(&id)
         .to_string();
-
-    fs::write("output.html", &html).unwrap();
 }
 
 fn criterion_benchmark(c: &mut Criterion) {
-    c.bench_function("main", |b| b.iter(|| benchmark()));
+    let json = fixture::page_chunk();
+    c.bench_function("main", |b| b.iter(|| benchmark(&json)));
+
+    // Compares the parser from before it borrowed strings (every string owned, and blocks that
+    // don't match the typings parsed twice) against borrowing them from the input, borrowing and
+    // then copying them with `into_owned`, and reading from a reader.
+    let mut group = c.benchmark_group("parse");
+    group.throughput(Throughput::Bytes(json.len() as u64));
+    group.bench_function("owned-before-borrowing", |b| {
+        b.iter_batched(|| json.clone(), |json| baseline_parser::parse(json).unwrap(), BatchSize::LargeInput)
+    });
+    group.bench_function("borrowed", |b| b.iter(|| parse(&json).unwrap()));
+    group.bench_function("borrowed-into-owned", |b| {
+        b.iter_batched(|| json.clone(), |json| parse_owned(json).unwrap(), BatchSize::LargeInput)
+    });
+    group.bench_function("reader", |b| b.iter(|| parse_reader(json.as_bytes()).unwrap()));
+    group.finish();
+
+    // Compares the two parsers on a page where some blocks don't match the typings. The baseline
+    // buffers every block to try it against the typings, and then parses the ones that don't match
+    // a second time, while the parser keeps them as JSON as it reads them.
+    let invalid = fixture::page_chunk_with_invalid_blocks();
+    let mut group = c.benchmark_group("parse-invalid-blocks");
+    group.throughput(Throughput::Bytes(invalid.len() as u64));
+    group.bench_function("owned-before-borrowing", |b| {
+        b.iter_batched(|| invalid.clone(), |json| baseline_parser::parse(json).unwrap(), BatchSize::LargeInput)
+    });
+    group.bench_function("borrowed", |b| b.iter(|| parse(&invalid).unwrap()));
+    group.finish();
+
+    // Compares rendering sibling blocks in order against rendering them in parallel.
+    let result = parse(&json).unwrap();
+    let id = BlockId::parse(fixture::ROOT_ID).unwrap();
+    let renderer = Renderer::new(&result.record_map.block, Blocks {}, Inline {}, Wrapper {});
+    let mut group = c.benchmark_group("render");
+    group.throughput(Throughput::Elements(result.record_map.block.len() as u64));
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            let html = renderer.render(&id);
+            renderer.take_diagnostics();
+            html
+        })
+    });
+    group.bench_function("parallel", |b| {
+        b.iter(|| {
+            let html = renderer.render_parallel(&id);
+            renderer.take_diagnostics();
+            html
+        })
+    });
+    group.finish();
+
+    // Escapes text with nothing to escape in it (which is most text), text that is mostly things to
+    // escape, and a short run like most of the ones on a page.
+    let plain = "Lorem ipsum dolor sit amet, consectetur adipiscing elit. ".repeat(64);
+    let markup = r#"<a href="x">Tom & Jerry's</a> "#.repeat(128);
+    let short = "a short run & ";
+    let mut group = c.benchmark_group("escape");
+    let inputs = [("escape-free", &plain[..]), ("escape-heavy", &markup[..]), ("short", short)];
+    for (name, input) in inputs {
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_function(name, |b| b.iter(|| escape(input)));
+    }
+    group.finish();
 }
 
 criterion_group!(benches, criterion_benchmark);
-criterion_main!(benches);
\ No newline at end of file
+criterion_main!(benches);
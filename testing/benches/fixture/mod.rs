@@ -0,0 +1,103 @@
+//! Builds the page the benchmarks parse and render, so they don't depend on a page saved from
+//! Notion. The page is made up, but shaped like a long real one: a few thousand blocks of mixed
+//! types right under the page, some with children, all holding formatted text and links.
+use serde_json::{json, Map, Value};
+
+/// The ID of the page.
+pub const ROOT_ID: &str = "ddda599f-ff69-4974-9dec-86f6abf3209a";
+
+const TYPES: [&str; 9] = [
+    "text",
+    "text",
+    "text",
+    "bulleted_list",
+    "numbered_list",
+    "header",
+    "quote",
+    "toggle",
+    "callout",
+];
+
+/// A small linear congruential generator, so the page is the same on every run.
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self, below: u64) -> u64 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (self.0 >> 33) % below
+    }
+}
+
+fn id(n: u64) -> String {
+    format!("{:08x}-0000-4000-8000-{:012x}", n, n)
+}
+
+fn block(id: &str, block_type: &str, parent_id: &str, content: &[String]) -> Value {
+    let mut value = json!({
+        "id": id,
+        "version": 1,
+        "type": block_type,
+        "properties": {
+            "title": [
+                ["Lorem ipsum dolor sit amet, consectetur adipiscing elit "],
+                ["bold <b>", [["b"], ["i"]]],
+                ["link", [["a", "https://example.com/a?b=c&d=e"]]],
+                [" more plain text \"quoted\" here."]
+            ]
+        },
+        "created_time": 1591012800000u64,
+        "last_edited_time": 1593604800000u64,
+        "parent_id": parent_id,
+        "parent_table": "block",
+        "alive": true,
+        "created_by_table": "notion_user",
+        "created_by_id": "ee5f0f84-409a-440f-983a-a5315961c6e4",
+        "last_edited_by_table": "notion_user",
+        "last_edited_by_id": "ee5f0f84-409a-440f-983a-a5315961c6e4",
+        "space_id": "5b2b5a32-1d2e-4a09-b0c5-b1bdfea1d4a0"
+    });
+    if !content.is_empty() {
+        value["content"] = json!(content);
+    }
+    json!({ "role": "reader", "value": value })
+}
+
+/// A `loadPageChunk` response holding the page (`ROOT_ID`) and about 7,500 blocks under it.
+pub fn page_chunk() -> String {
+    let mut random = Random(1);
+    let mut blocks = Map::new();
+    let mut children = vec![];
+    let mut n = 1;
+
+    for _ in 0..3000 {
+        n += 1;
+        let parent = id(n);
+        let mut content = vec![];
+        for _ in 0..random.next(4) {
+            n += 1;
+            let child = id(n);
+            let block_type = TYPES[random.next(TYPES.len() as u64) as usize];
+            blocks.insert(child.clone(), block(&child, block_type, &parent, &[]));
+            content.push(child);
+        }
+
+        let block_type = TYPES[random.next(TYPES.len() as u64) as usize];
+        blocks.insert(parent.clone(), block(&parent, block_type, ROOT_ID, &content));
+        children.push(parent);
+    }
+    blocks.insert(ROOT_ID.to_string(), block(ROOT_ID, "page", &id(0), &children));
+
+    json!({ "recordMap": { "block": blocks } }).to_string()
+}
+
+/// The same page, but with every tenth block's text broken (a number where a string should be),
+/// so those blocks fall back to their raw JSON.
+pub fn page_chunk_with_invalid_blocks() -> String {
+    let mut chunk: Value = serde_json::from_str(&page_chunk()).unwrap();
+    let blocks = chunk["recordMap"]["block"].as_object_mut().unwrap();
+    for (_, block) in blocks.iter_mut().step_by(10) {
+        block["value"]["properties"]["title"][1][0] = json!(1);
+    }
+
+    chunk.to_string()
+}
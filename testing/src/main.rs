@@ -1,4 +1,4 @@
-use base::parser::parse;
+use base::id::BlockId;
 use base::renderer::Renderer;
 use std::fs;
 use ui::{Blocks, Inline, Wrapper};
@@ -7,12 +7,17 @@ use api::load_page_chunks::load;
 #[tokio::main]
 async fn main() {
     println!("Starting now!");
-    let result = load("ddda599f-ff69-4974-9dec-86f6abf3209a").await.unwrap();
+    let id = BlockId::parse("ddda599f-ff69-4974-9dec-86f6abf3209a").unwrap();
+    let result = load(&id).await.unwrap();
     let renderer = Renderer::new(&result.record_map.block, Blocks {}, Inline {}, Wrapper {});
     println!("Starting rendering... now!!");
     let html = renderer
-        .render("ddda599f-ff69-4974-9dec-86f6abf3209a")
+        .render(&id)
         .to_string();
 
+    for diagnostic in renderer.take_diagnostics() {
+        println!("Skipped {}", diagnostic);
+    }
+
     fs::write("output.html", &html).unwrap();
 }
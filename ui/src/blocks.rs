@@ -1,5 +1,6 @@
-use base::renderer::BlockRenderer;
+use base::renderer::{BlockContext, BlockRenderer, ListPosition};
 use templating::attributes::*;
+use templating::html;
 use templating::tags::*;
 
 pub struct Blocks {}
@@ -10,49 +11,72 @@ fn wrapper(children: Tag, text: Option<Tag>, c: &str, root: TagType, wrapper: Ta
             vec![class(c)],
             vec![option_include(text)]
         ),
-        wrapper(vec![], vec![children]),
+        children_wrapper(children, wrapper),
     ])
 }
 
+/// Wraps a block's children, leaving the wrapper out if there aren't any.
+fn children_wrapper(children: Tag, wrapper: TagType) -> Tag {
+    if children.is_empty() {
+        empty()
+    } else {
+        wrapper(vec![], vec![children])
+    }
+}
+
 impl BlockRenderer<Tag> for Blocks {
-    fn page_block(&self, children: Tag, text: Option<Tag>) -> Tag {
+    fn page_block(&self, _context: &BlockContext, children: Tag, text: Option<Tag>) -> Tag {
         wrapper(children, text, "notion-page-block", h1, div)
     }
 
-    fn text_block(&self, children: Tag, text: Option<Tag>) -> Tag {
+    fn text_block(&self, _context: &BlockContext, children: Tag, text: Option<Tag>) -> Tag {
         wrapper(children, text, "notion-text", p, div)
     }
 
-    fn bulleted_list_block(&self, children: Tag, text: Option<Tag>) -> Tag {
+    fn bulleted_list_block(&self, _context: &BlockContext, _position: ListPosition, children: Tag, text: Option<Tag>) -> Tag {
         wrapper(children, text, "notion-bulleted_list-block", li, div)
     }
 
-    fn numbered_list_block(&self, children: Tag, text: Option<Tag>) -> Tag {
-        wrapper(children, text, "notion-bulleted_list-block", li, div)
+    fn numbered_list_block(&self, _context: &BlockContext, _position: ListPosition, children: Tag, text: Option<Tag>) -> Tag {
+        wrapper(children, text, "notion-numbered_list-block", li, div)
     }
 
-    fn toggle_block(&self, children: Tag, text: Option<Tag>) -> Tag {
-        wrapper(children, text, "notion-toggle-block", |a, b| { details(a, vec![summary(vec![], b)]) }, summary)
+    fn toggle_block(&self, _context: &BlockContext, children: Tag, text: Option<Tag>) -> Tag {
+        details(
+            vec![class("notion-toggle-block")],
+            vec![
+                summary(vec![], vec![option_include(text)]),
+                children_wrapper(children, div),
+            ]
+        )
     }
 
-    fn quote_block(&self, children: Tag, text: Option<Tag>) -> Tag {
+    fn quote_block(&self, _context: &BlockContext, children: Tag, text: Option<Tag>) -> Tag {
         wrapper(children, text, "notion-quote-block", q, div)
     }
 
-    fn header_block(&self, children: Tag, text: Option<Tag>) -> Tag {
+    fn header_block(&self, _context: &BlockContext, children: Tag, text: Option<Tag>) -> Tag {
         wrapper(children, text, "notion-header-block", h1, div)
     }
 
-    fn sub_header_block(&self, children: Tag, text: Option<Tag>) -> Tag {
+    fn sub_header_block(&self, _context: &BlockContext, children: Tag, text: Option<Tag>) -> Tag {
         wrapper(children, text, "notion-sub_header-block", h2, div)
     }
 
-    fn sub_sub_header_block(&self, children: Tag, text: Option<Tag>) -> Tag {
+    fn sub_sub_header_block(&self, _context: &BlockContext, children: Tag, text: Option<Tag>) -> Tag {
         wrapper(children, text, "notion-sub_sub_header-block", h3, div)
     }
 
-    fn divider_block(&self, children: Tag) -> Tag {
-        hr(vec![class("notion-divider-block")], vec![children])
+    fn divider_block(&self, _context: &BlockContext, children: Tag) -> Tag {
+        // `hr` can't have children, so anything under the divider goes after it.
+        collect(vec![
+            html::hr().class("notion-divider-block").build(),
+            children,
+        ])
+    }
+
+    fn unknown_block(&self, _context: &BlockContext, children: Tag, type_name: &str) -> Tag {
+        div(vec![class("notion-unknown-block"), attribute("data-block-type", type_name)], vec![children])
     }
 
     fn empty(&self) -> Tag {
@@ -31,7 +31,7 @@ impl InlineRenderer<Tag> for Inline {
         code(vec![class("notion-code-inline")], vec![acc])
     }
 
-    fn link(&self, acc: Tag, link: &String) -> Tag {
+    fn link(&self, acc: Tag, link: &str) -> Tag {
         a(vec![class("notion-link"), href(link)], vec![acc])
     }
 
@@ -1,9 +1,11 @@
 mod blocks;
 mod inline;
+mod stream;
 mod wrapper;
 mod utils;
 
 pub use blocks::Blocks;
 pub use inline::Inline;
+pub use stream::HtmlStream;
 pub use templating::tags::Tag;
 pub use wrapper::Wrapper;
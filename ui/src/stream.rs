@@ -0,0 +1,131 @@
+use base::parser::{ContextFormat, FormatType, NoContextFormat, RootBlockType};
+use base::renderer::{BlockContext, ListPosition};
+use base::stream::{ListKind, StreamRenderer};
+use std::fmt::{self, Write};
+use templating::attributes::UrlSanitizer;
+use templating::tags::escape;
+use crate::utils::color_class_name;
+use crate::wrapper::numbering_type;
+
+/// Writes the same HTML as `Blocks`, `Inline` and `Wrapper` for `StreamingRenderer`. A block's
+/// children are wrapped (eg. in `<div></div>`) if it lists any in its `content`, even if none of
+/// them end up being rendered.
+pub struct HtmlStream {}
+
+/// Whether a block has children to wrap, like `children_wrapper` checks for `Blocks`.
+fn has_children(context: &BlockContext) -> bool {
+    context.block.content.as_deref().is_some_and(|c| !c.is_empty())
+}
+
+/// The tag and class a block's text is written in, and the tag its children are written in.
+fn block_tags(block: &RootBlockType) -> Option<(&'static str, &'static str, &'static str)> {
+    match block {
+        RootBlockType::Page { .. } => Some(("h1", "notion-page-block", "div")),
+        RootBlockType::Text { .. } => Some(("p", "notion-text", "div")),
+        RootBlockType::BulletedList { .. } => Some(("li", "notion-bulleted_list-block", "div")),
+        RootBlockType::NumberedList { .. } => Some(("li", "notion-numbered_list-block", "div")),
+        RootBlockType::Quote { .. } => Some(("q", "notion-quote-block", "div")),
+        RootBlockType::Header { .. } => Some(("h1", "notion-header-block", "div")),
+        RootBlockType::SubHeader { .. } => Some(("h2", "notion-sub_header-block", "div")),
+        RootBlockType::SubSubHeader { .. } => Some(("h3", "notion-sub_sub_header-block", "div")),
+        _ => None,
+    }
+}
+
+/// The tag and class an inline style is written as, if it is supported.
+fn format_tag(format: &FormatType) -> Option<(&'static str, &'static str)> {
+    match format {
+        FormatType::NoContext(NoContextFormat::Bold) => Some(("b", "notion-bold")),
+        FormatType::NoContext(NoContextFormat::Italic) => Some(("i", "notion-italic")),
+        FormatType::NoContext(NoContextFormat::Underline) => Some(("u", "notion-underline")),
+        FormatType::NoContext(NoContextFormat::Strike) => Some(("strike", "notion-strike")),
+        FormatType::NoContext(NoContextFormat::Code) => Some(("code", "notion-code-inline")),
+        FormatType::Context(ContextFormat::Link(_)) => Some(("a", "notion-link")),
+        FormatType::Context(ContextFormat::Highlight(color)) => Some(("span", color_class_name(color))),
+        _ => None,
+    }
+}
+
+impl StreamRenderer for HtmlStream {
+    fn open_block(&self, out: &mut dyn Write, context: &BlockContext, _position: ListPosition) -> fmt::Result {
+        match &context.block.block {
+            RootBlockType::Toggle { .. } => out.write_str(r#"<details class="notion-toggle-block"><summary>"#),
+            RootBlockType::Divider => out.write_str(r#"<hr class="notion-divider-block">"#),
+            RootBlockType::Unknown { type_name, .. } => {
+                write!(out, r#"<div class="notion-unknown-block" data-block-type="{}">"#, escape(type_name))
+            }
+            block => match block_tags(block) {
+                Some((root, class, _)) => write!(out, r#"<{} class="{}">"#, root, class),
+                None => Ok(()),
+            },
+        }
+    }
+
+    fn open_children(&self, out: &mut dyn Write, context: &BlockContext) -> fmt::Result {
+        let children = has_children(context);
+        match &context.block.block {
+            RootBlockType::Toggle { .. } if children => out.write_str("</summary><div>"),
+            RootBlockType::Toggle { .. } => out.write_str("</summary>"),
+            block => match block_tags(block) {
+                Some((root, _, wrapper)) if children => write!(out, "</{}><{}>", root, wrapper),
+                Some((root, _, _)) => write!(out, "</{}>", root),
+                None => Ok(()),
+            },
+        }
+    }
+
+    fn close_block(&self, out: &mut dyn Write, context: &BlockContext) -> fmt::Result {
+        let children = has_children(context);
+        match &context.block.block {
+            RootBlockType::Toggle { .. } if children => out.write_str("</div></details>"),
+            RootBlockType::Toggle { .. } => out.write_str("</details>"),
+            RootBlockType::Unknown { .. } => out.write_str("</div>"),
+            block => match block_tags(block) {
+                Some((_, _, wrapper)) if children => write!(out, "</{}>", wrapper),
+                _ => Ok(()),
+            },
+        }
+    }
+
+    fn open_list(&self, out: &mut dyn Write, kind: ListKind, first: ListPosition) -> fmt::Result {
+        match kind {
+            ListKind::Bulleted => out.write_str(r#"<ul class="notion-bulleted_list-wrapper">"#),
+            ListKind::Numbered => {
+                write!(out, r#"<ol class="notion-numbered_list-wrapper" type="{}""#, numbering_type(first.level))?;
+                if first.ordinal != 1 {
+                    write!(out, r#" start="{}""#, first.ordinal)?;
+                }
+                out.write_str(">")
+            }
+        }
+    }
+
+    fn close_list(&self, out: &mut dyn Write, kind: ListKind) -> fmt::Result {
+        match kind {
+            ListKind::Bulleted => out.write_str("</ul>"),
+            ListKind::Numbered => out.write_str("</ol>"),
+        }
+    }
+
+    fn text(&self, out: &mut dyn Write, text: &str) -> fmt::Result {
+        out.write_str(&escape(text))
+    }
+
+    fn open_format(&self, out: &mut dyn Write, format: &FormatType) -> fmt::Result {
+        match (format, format_tag(format)) {
+            (FormatType::Context(ContextFormat::Link(link)), Some((tag, class))) => {
+                let link = UrlSanitizer::default().sanitize(link);
+                write!(out, r#"<{} class="{}" href="{}">"#, tag, class, escape(link))
+            }
+            (_, Some((tag, class))) => write!(out, r#"<{} class="{}">"#, tag, class),
+            (_, None) => Ok(()),
+        }
+    }
+
+    fn close_format(&self, out: &mut dyn Write, format: &FormatType) -> fmt::Result {
+        match format_tag(format) {
+            Some((tag, _)) => write!(out, "</{}>", tag),
+            None => Ok(()),
+        }
+    }
+}
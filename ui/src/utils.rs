@@ -1,6 +1,6 @@
 use base::parser::ColorType;
 
-pub fn color_class_name(c: &ColorType) -> &str {
+pub fn color_class_name(c: &ColorType) -> &'static str {
     match c {
         ColorType::Gray => "notion-gray",
         ColorType::Brown => "notion-brown",
@@ -20,6 +20,6 @@ pub fn color_class_name(c: &ColorType) -> &str {
         ColorType::PurpleBackground => "notion-purple_background",
         ColorType::PinkBackground => "notion-pink_background",
         ColorType::RedBackground => "notion-red_background",
-        ColorType::None => ""
+        ColorType::None | ColorType::Unknown(_) => ""
     }
 }
\ No newline at end of file
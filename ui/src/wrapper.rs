@@ -1,4 +1,4 @@
-use base::renderer::WrapperRenderer;
+use base::renderer::{ListPosition, WrapperRenderer};
 use templating::attributes::*;
 use templating::tags::*;
 
@@ -8,13 +8,26 @@ pub fn wrapper(items: Vec<Tag>, c: &str, elem: TagType) -> Tag {
     elem(vec![class(c)], items)
 }
 
+/// The `type` of an `ol` at a nesting level. Notion numbers nested lists as 1, a, i, 1, ...
+pub(crate) fn numbering_type(level: usize) -> &'static str {
+    match level % 3 {
+        0 => "1",
+        1 => "a",
+        _ => "i",
+    }
+}
+
 impl WrapperRenderer<Tag> for Wrapper {
-    fn bulleted_list_wrapper(&self, items: Vec<Tag>) -> Tag {
+    fn bulleted_list_wrapper(&self, items: Vec<Tag>, _first: ListPosition) -> Tag {
         wrapper(items, "notion-bulleted_list-wrapper", ul)
     }
 
-    fn numbered_list_wrapper(&self, items: Vec<Tag>) -> Tag {
-        wrapper(items, "notion-numbered_list-wrapper", ol)
+    fn numbered_list_wrapper(&self, items: Vec<Tag>, first: ListPosition) -> Tag {
+        let mut attributes = vec![class("notion-numbered_list-wrapper"), r#type(numbering_type(first.level))];
+        if first.ordinal != 1 {
+            attributes.push(start(&first.ordinal.to_string()));
+        }
+        ol(attributes, items)
     }
 
     fn collect(&self, items: Vec<Tag>) -> Tag {
@@ -0,0 +1,39 @@
+//! Builds `loadPageChunk` responses for the tests, out of blocks that only hold text.
+#![allow(dead_code)]
+
+use base::id::BlockId;
+use serde_json::{json, Map, Value};
+
+pub fn id(n: u32) -> BlockId {
+    BlockId::parse(&format!("00000000-0000-4000-8000-{:012}", n)).unwrap()
+}
+
+/// Block `n`, of the type `block_type`, holding `text` and the children `content`.
+pub fn block(n: u32, block_type: &str, text: &str, content: &[u32]) -> Value {
+    json!({
+        "id": id(n),
+        "version": 1,
+        "type": block_type,
+        "properties": { "title": [[text]] },
+        "content": content.iter().map(|c| id(*c)).collect::<Vec<_>>(),
+        "created_time": 0,
+        "last_edited_time": 0,
+        "parent_id": id(0),
+        "parent_table": "block",
+        "alive": true,
+        "created_by_table": "notion_user",
+        "created_by_id": "",
+        "last_edited_by_table": "notion_user",
+        "last_edited_by_id": ""
+    })
+}
+
+/// A `loadPageChunk` response holding `blocks`.
+pub fn chunk(blocks: Vec<Value>) -> String {
+    let mut table = Map::new();
+    for value in blocks {
+        let id = value["id"].as_str().unwrap().to_string();
+        table.insert(id, json!({ "role": "reader", "value": value }));
+    }
+    json!({ "recordMap": { "block": table } }).to_string()
+}
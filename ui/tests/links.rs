@@ -0,0 +1,42 @@
+use base::parser::{BlockTableType, FormattedText};
+use base::renderer::Renderer;
+use base::stream::StreamingRenderer;
+use std::collections::HashMap;
+use ui::{Blocks, HtmlStream, Inline, Wrapper};
+
+/// Rich text the way Notion stores it: runs of text, each with a list of formats. `a` is a link.
+const TITLE: &str = r#"[
+    ["quote", [["a", "https://example.com/\"><script>alert(1)</script>"]]],
+    ["script", [["a", "javascript:alert(document.cookie)"]]],
+    ["tab", [["a", " java\tscript:alert(1)"]]],
+    ["safe", [["a", "https://example.com/a?b=c&d=e"]]],
+    ["<img src=x onerror=alert(1)>", [["b"]]]
+]"#;
+
+const EXPECTED: &str = concat!(
+    r#"<a class="notion-link" href="https://example.com/&quot;&gt;&lt;script&gt;alert(1)&lt;/script&gt;">quote</a>"#,
+    r##"<a class="notion-link" href="#">script</a>"##,
+    r##"<a class="notion-link" href="#">tab</a>"##,
+    r#"<a class="notion-link" href="https://example.com/a?b=c&amp;d=e">safe</a>"#,
+    r#"<b class="notion-bold">&lt;img src=x onerror=alert(1)&gt;</b>"#,
+);
+
+fn title() -> Vec<FormattedText<'static>> {
+    serde_json::from_str(TITLE).unwrap()
+}
+
+#[test]
+fn escapes_and_sanitizes_links() {
+    let blocks: BlockTableType = HashMap::new();
+    let renderer = Renderer::new(&blocks, Blocks {}, Inline {}, Wrapper {});
+    assert_eq!(renderer.render_text(&title()).to_string(), EXPECTED);
+}
+
+#[test]
+fn streams_the_same_links() {
+    let blocks: BlockTableType = HashMap::new();
+    let renderer = StreamingRenderer::new(&blocks, HtmlStream {});
+    let mut out = String::new();
+    renderer.render_text(&title(), &mut out).unwrap();
+    assert_eq!(out, EXPECTED);
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use base::parser::parse;
+use base::renderer::Renderer;
+use base::stream::StreamingRenderer;
+use common::{block, chunk, id};
+use serde_json::Value;
+use ui::{Blocks, HtmlStream, Inline, Wrapper};
+
+/// Renders block 0 of `blocks`, checking that it streams the same way, and returns the opening
+/// tag of each list in it, in order.
+fn lists(blocks: Vec<Value>) -> Vec<String> {
+    let input = chunk(blocks);
+    let data = parse(&input).unwrap();
+    let renderer = Renderer::new(&data.record_map.block, Blocks {}, Inline {}, Wrapper {});
+    let html = renderer.render(&id(0)).to_string();
+
+    let streaming = StreamingRenderer::new(&data.record_map.block, HtmlStream {});
+    let mut streamed = String::new();
+    streaming.render(&id(0), &mut streamed).unwrap();
+    assert_eq!(streamed, html);
+
+    html.match_indices('<')
+        .map(|(start, _)| &html[start..])
+        .filter(|tag| tag.starts_with("<ol") || tag.starts_with("<ul"))
+        .map(|tag| tag[..=tag.find('>').unwrap()].to_string())
+        .collect()
+}
+
+const BULLETED: &str = r#"<ul class="notion-bulleted_list-wrapper">"#;
+
+fn numbered(numbering: &str, start: Option<usize>) -> String {
+    let start = start
+        .map(|s| format!(r#" start="{}""#, s))
+        .unwrap_or_default();
+    format!(
+        r#"<ol class="notion-numbered_list-wrapper" type="{}"{}>"#,
+        numbering, start
+    )
+}
+
+#[test]
+fn carries_numbering_on_after_a_paragraph() {
+    let lists = lists(vec![
+        block(0, "page", "Page", &[1, 2, 3, 4]),
+        block(1, "numbered_list", "one", &[]),
+        block(2, "numbered_list", "two", &[]),
+        block(3, "text", "An interruption", &[]),
+        block(4, "numbered_list", "three", &[]),
+    ]);
+    assert_eq!(lists, [numbered("1", None), numbered("1", Some(3))]);
+}
+
+#[test]
+fn carries_numbering_on_after_a_bulleted_item() {
+    let lists = lists(vec![
+        block(0, "page", "Page", &[1, 2, 3]),
+        block(1, "numbered_list", "one", &[]),
+        block(2, "bulleted_list", "bullet", &[]),
+        block(3, "numbered_list", "two", &[]),
+    ]);
+    assert_eq!(
+        lists,
+        [
+            numbered("1", None),
+            BULLETED.to_string(),
+            numbered("1", Some(2))
+        ]
+    );
+}
+
+#[test]
+fn numbers_nested_levels_differently() {
+    let lists = lists(vec![
+        block(0, "page", "Page", &[1]),
+        block(1, "numbered_list", "1", &[2]),
+        block(2, "numbered_list", "1.a", &[3]),
+        block(3, "numbered_list", "1.a.i", &[4]),
+        block(4, "numbered_list", "1.a.i.1", &[]),
+    ]);
+    assert_eq!(
+        lists,
+        [
+            numbered("1", None),
+            numbered("a", None),
+            numbered("i", None),
+            numbered("1", None)
+        ]
+    );
+}
+
+#[test]
+fn starts_over_under_a_bulleted_item() {
+    let lists = lists(vec![
+        block(0, "page", "Page", &[1]),
+        block(1, "numbered_list", "1", &[2]),
+        block(2, "bulleted_list", "bullet", &[3]),
+        block(3, "numbered_list", "back to 1", &[]),
+    ]);
+    assert_eq!(
+        lists,
+        [
+            numbered("1", None),
+            BULLETED.to_string(),
+            numbered("1", None)
+        ]
+    );
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use base::parser::parse;
+use base::renderer::Renderer;
+use base::stream::StreamingRenderer;
+use common::{block, chunk, id};
+use std::io;
+use ui::{Blocks, HtmlStream, Inline, Wrapper};
+
+/// A page with every kind of block `HtmlStream` writes, with and without children.
+fn page() -> String {
+    chunk(vec![
+        block(0, "page", "Page", &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]),
+        block(1, "text", "Leaf", &[]),
+        block(2, "text", "Parent", &[20]),
+        block(20, "text", "Child", &[]),
+        block(3, "bulleted_list", "a", &[]),
+        block(4, "bulleted_list", "b", &[21]),
+        block(21, "numbered_list", "b.1", &[]),
+        block(5, "numbered_list", "one", &[]),
+        block(6, "numbered_list", "two", &[]),
+        block(7, "toggle", "Closed", &[]),
+        block(8, "toggle", "Open", &[22]),
+        block(22, "quote", "Inside", &[]),
+        block(9, "header", "Header", &[]),
+        block(10, "divider", "", &[23]),
+        block(23, "sub_header", "Under the divider", &[]),
+        block(11, "callout", "Unknown", &[24]),
+        block(24, "sub_sub_header", "In the callout", &[]),
+    ])
+}
+
+#[test]
+fn streams_the_same_html_as_the_renderer() {
+    let input = page();
+    let data = parse(&input).unwrap();
+    let blocks = &data.record_map.block;
+
+    let renderer = Renderer::new(blocks, Blocks {}, Inline {}, Wrapper {});
+    let expected = renderer.render(&id(0)).to_string();
+
+    let streaming = StreamingRenderer::new(blocks, HtmlStream {});
+    let mut out = String::new();
+    streaming.render(&id(0), &mut out).unwrap();
+
+    assert!(!expected.is_empty());
+    assert_eq!(out, expected);
+    assert_eq!(streaming.take_diagnostics(), renderer.take_diagnostics());
+
+    let mut bytes = vec![];
+    streaming.render_io(&id(0), &mut bytes).unwrap();
+    assert_eq!(String::from_utf8(bytes).unwrap(), expected);
+}
+
+/// A writer whose reader has gone away.
+struct Closed;
+
+impl io::Write for Closed {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::Error::new(
+            io::ErrorKind::BrokenPipe,
+            "the reader is gone",
+        ))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn passes_write_errors_through() {
+    let input = page();
+    let data = parse(&input).unwrap();
+    let streaming = StreamingRenderer::new(&data.record_map.block, HtmlStream {});
+
+    let error = streaming.render_io(&id(0), Closed).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::BrokenPipe);
+    assert_eq!(error.to_string(), "the reader is gone");
+}